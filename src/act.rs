@@ -1,4 +1,6 @@
+use crate::{Arrive, Blame, Excuse, MIN_SPAN};
 use convert_case::Casing;
+use strum::IntoEnumIterator;
 
 /// The `act` module provides the `Act` enum for encapsulating action handling.
 /// We match keyboard events to variants of the `Act` enum, then dispatch events handlers by matching against the `Act` enum.
@@ -18,13 +20,58 @@ use convert_case::Casing;
 /// into a [`config::Config`] struct contained in the `config` field of [`crate::App`],
 /// and will warn the user if no mappings return and substitute a default configuration instead.
 ///
-/// Modifers are not currently supported, so only use single characters as `value` arguments.
+/// The `value` is a [`crate::Chord`], a single character or named key with optional modifiers,
+/// like `"n"`, `"Ctrl+n"` or `"Shift+Escape"`.
 ///
 /// ## Update 0.1.1
 ///
 /// The `Act` enum now includes a `CloseWindow` variant, indicating the user intent to close the
 /// window.  Once we successfully created background processes to spawn new windows, the need to
 /// subsequently close windows became clear.
+///
+/// The `Upvote` and `Downvote` variants let the user rate the most recent quote vandalized by an
+/// imp.  Ratings persist in a [`crate::Ledger`], and the imps favor the quotes the user likes.
+///
+/// The `SwitchMode` variant carries the name of a mode, so one variant covers every mode the user
+/// defines.  Its bindings live in a `switch_mode` table rather than a single key, see
+/// [`crate::Cmd`].
+///
+/// The `Rebind` variant starts a [`crate::Capture`], letting the user bind a new key to an act
+/// without leaving the program, and the `ShowHelp` variant logs a cheat sheet of the bindings,
+/// see [`crate::Sheet`].
+///
+/// The `Macro` variant plays a series of acts defined by the user in the `[macros]` table of
+/// `Tardy.toml`, see [`crate::Routine`].  The `Record`, `StopRecording` and `Replay` variants
+/// record the acts of the user as they happen, and play them back later, see
+/// [`crate::Registers`].
+///
+/// The `Custom` variant carries the name of an act added by a library user, answered by the
+/// handler registered under that name, see [`crate::Dispatcher`].
+///
+/// ## Parameters
+///
+/// Some acts take parameters, like the number of windows to open, or the size to give a window.
+/// Since a key in `Tardy.toml` has no room for parameters, these bindings go in a `[[binds]]`
+/// table instead, one per binding, where the `act` field names the act in snake case and the
+/// other fields hold the parameters:
+///
+/// ```toml
+/// [[binds]]
+/// keys = "Ctrl+n"
+/// act = "new_window"
+/// count = 3
+///
+/// [[binds]]
+/// keys = ["r", "Ctrl+r"]
+/// act = "resize_window"
+/// width = 800
+/// height = 600
+/// ```
+///
+/// We read the tables using the [`serde`] derive on `Act`, with the variant name as an internal
+/// tag in the `act` field, then check the parameters make sense with [`Act::validate`].  Acts
+/// without parameters can go in a `[[binds]]` table too, though the usual `exit = "Escape"` is
+/// shorter.
 #[derive(
     Debug,
     Default,
@@ -39,13 +86,61 @@ use convert_case::Casing;
     serde::Serialize,
     serde::Deserialize,
 )]
+#[serde(tag = "act", rename_all = "snake_case")]
 pub enum Act {
     /// The `CloseWindow` variant indicates the user would like to close the current window.
     CloseWindow,
     /// The `Exit` variant indicates the user would like to close the current window.
     Exit,
-    /// The `NewWindow` variant indicates the user would like to create a new window.
-    NewWindow,
+    /// The `NewWindow` variant indicates the user would like to create a new window, or `count`
+    /// new windows, up to [`MAX_COUNT`].
+    #[display("NewWindow")]
+    NewWindow {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        count: Option<usize>,
+    },
+    /// The `ResizeWindow` variant indicates the user would like to resize the current window to
+    /// `width` by `height` physical pixels, at least [`MIN_SPAN`] each way.
+    #[display("ResizeWindow")]
+    ResizeWindow { width: u32, height: u32 },
+    /// The `Upvote` variant indicates the user liked the most recent quote.
+    Upvote,
+    /// The `Downvote` variant indicates the user would rather not see the most recent quote again.
+    Downvote,
+    /// The `SwitchMode` variant indicates the user would like to switch to the named mode, see
+    /// [`crate::Cmd::switch`].
+    #[display("SwitchMode")]
+    SwitchMode { mode: String },
+    /// The `Rebind` variant indicates the user would like to bind a new key to an act, see
+    /// [`crate::Capture`].
+    Rebind,
+    /// The `ShowHelp` variant indicates the user would like to see which keys do what.
+    ShowHelp,
+    /// The `Macro` variant indicates the user would like to play the macro called `name`, see
+    /// [`crate::Routine`].
+    #[display("Macro")]
+    Macro { name: String },
+    /// The `Record` variant indicates the user would like to start recording acts, see
+    /// [`crate::Recorder`].
+    Record,
+    /// The `StopRecording` variant indicates the user would like to stop recording, and keep the
+    /// recording in the named `register`, or the [`crate::DEFAULT_REGISTER`].
+    #[display("StopRecording")]
+    StopRecording {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        register: Option<String>,
+    },
+    /// The `Replay` variant indicates the user would like to replay the recording in the named
+    /// `register`, or the [`crate::DEFAULT_REGISTER`].
+    #[display("Replay")]
+    Replay {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        register: Option<String>,
+    },
+    /// The `Custom` variant indicates the user would like to perform the act registered under
+    /// `name` with [`crate::App::register`].
+    #[display("Custom")]
+    Custom { name: String },
     /// The `Be` variant does nothing.
     #[default]
     Be,
@@ -75,4 +170,94 @@ impl Act {
     pub fn snake(&self) -> String {
         self.to_string().to_case(convert_case::Case::Snake)
     }
+
+    /// The `name` method returns the name of the handler for the act in a [`crate::Dispatcher`],
+    /// which is the [`Act::snake`] name, or the `name` carried by [`Act::Custom`].
+    pub fn name(&self) -> String {
+        match self {
+            Self::Custom { name } => name.clone(),
+            _ => self.snake(),
+        }
+    }
+
+    /// The `describe` method returns a sentence describing what the act does, for the cheat
+    /// sheets rendered by [`crate::Sheet`].
+    pub fn describe(&self) -> String {
+        match self {
+            Self::CloseWindow => "Close the current window.".to_string(),
+            Self::Exit => "Close every window and quit.".to_string(),
+            Self::NewWindow { count: None } => "Open a new window.".to_string(),
+            Self::NewWindow { count: Some(count) } => format!("Open {count} new windows."),
+            Self::ResizeWindow { width, height } => {
+                format!("Resize the current window to {width}x{height}.")
+            }
+            Self::Upvote => "Rate the last quote up, so the imps pick it more often.".to_string(),
+            Self::Downvote => {
+                "Rate the last quote down, so the imps pick it less often.".to_string()
+            }
+            Self::SwitchMode { mode } => format!("Switch to {mode} mode."),
+            Self::Rebind => "Pick an act and press a new key for it.".to_string(),
+            Self::ShowHelp => "Show this cheat sheet.".to_string(),
+            Self::Macro { name } => format!("Play the {name} macro."),
+            Self::Record => "Start recording acts.".to_string(),
+            Self::StopRecording { register } => format!(
+                "Stop recording, and keep it in the {} register.",
+                crate::Registers::name(register.as_deref())
+            ),
+            Self::Replay { register } => format!(
+                "Replay the recording in the {} register.",
+                crate::Registers::name(register.as_deref())
+            ),
+            Self::Custom { name } => format!("Perform the {name} act."),
+            Self::Be => "Do nothing.".to_string(),
+        }
+    }
+
+    /// The `is_plain` method returns `true` if the act carries no parameters beyond its name, so
+    /// it can be bound by name at the top of `Tardy.toml`.
+    pub fn is_plain(&self) -> bool {
+        Self::iter().any(|act| act == *self)
+    }
+
+    /// The `is_recordable` method returns `true` if a [`crate::Recorder`] should keep the act.
+    /// Acts that record, replay or play macros would start a tangle of replays within replays,
+    /// and [`Act::Rebind`] would stop a replay cold waiting for keys, so we leave them out.
+    pub fn is_recordable(&self) -> bool {
+        !matches!(
+            self,
+            Self::Macro { .. }
+                | Self::Record
+                | Self::StopRecording { .. }
+                | Self::Replay { .. }
+                | Self::Rebind
+                | Self::Be
+        )
+    }
+
+    /// The `validate` method checks the parameters of the act make sense.
+    ///
+    /// Will [`Excuse::Parameter`] if `count` is zero or more than [`MAX_COUNT`], if the `width`
+    /// or `height` is less than [`MIN_SPAN`], if a macro has no name, or if a custom act has no
+    /// name or takes the name of a built-in act.
+    pub fn validate(&self) -> Arrive<()> {
+        let problem = match self {
+            Self::NewWindow { count: Some(count) } if *count == 0 || *count > MAX_COUNT => {
+                format!("new_window count = {count} (1 to {MAX_COUNT})")
+            }
+            Self::ResizeWindow { width, height } if *width < MIN_SPAN || *height < MIN_SPAN => {
+                format!("resize_window {width}x{height} (at least {MIN_SPAN} each way)")
+            }
+            Self::Macro { name } if name.is_empty() => "macro with no name".to_string(),
+            Self::Custom { name } if name.is_empty() => "custom act with no name".to_string(),
+            Self::Custom { name } if Self::iter().any(|act| act.snake() == *name) => {
+                format!("custom act {name} takes the name of a built-in act")
+            }
+            _ => return Ok(()),
+        };
+        Err(Blame::Excuse(Excuse::Parameter(problem)))
+    }
 }
+
+/// The `MAX_COUNT` constant holds the most windows a single [`Act::NewWindow`] may open, so a slip
+/// of the finger in `Tardy.toml` does not bury the desktop.
+pub const MAX_COUNT: usize = 10;
@@ -1,12 +1,18 @@
-use crate::{Act, Arrive, Cmd, Hijinks, ImpKing, Lens};
+use crate::{
+    Act, Arrive, Capture, Chord, Cmd, Deed, Dispatcher, Entry, Hijinks, ImpKing, Journal, Ledger,
+    Lens, Lore, Meddle, Origin, Preset, Quote, Recorder, Registers, Routine, Scrawls, Sheet, Step,
+    Tempers, CONFIG_PATH, DEFAULT_KEYMAP,
+};
 use rand::Rng;
 use std::collections::HashMap;
 use std::sync::Arc;
+use strum::IntoEnumIterator;
+use tokio::sync::watch;
 use winit::application::ApplicationHandler;
 use winit::{
     dpi,
     event::{self, WindowEvent},
-    event_loop, monitor, window,
+    event_loop, keyboard, monitor, window,
 };
 
 /// The `app` module contains the `App` struct, which holds the parent-level top view of the
@@ -45,18 +51,51 @@ use winit::{
 /// from the async process back to the sync event loop as a user event of type `Hijinks`.
 #[derive(Debug)]
 pub struct App {
+    capture: Option<Capture>,
     cmd: Cmd,
     config: config::Config,
+    dispatcher: Dispatcher,
+    imp_keys: Option<Cmd>,
+    last_quote: Option<Quote>,
+    ledger: Ledger,
+    lore: Lore,
+    modifiers: keyboard::ModifiersState,
     proxy: event_loop::EventLoopProxy<Hijinks>,
+    ratings: watch::Sender<Ledger>,
+    recorder: Option<Recorder>,
+    registers: Registers,
+    routine: Option<tokio::task::JoinHandle<()>>,
+    scrawls: Scrawls,
+    tempers: Tempers,
     windows: HashMap<window::WindowId, Lens>,
 }
 
 /// ### Fields
 ///
+/// * The `capture` field holds the [`Capture`] in progress while the user rebinds a key, which
+///   claims all keyboard input until it finishes.
 /// * The `cmd` field holds the [`Cmd`] struct, which maps keyboard inputs to program responses.
 /// * The `config` field holds the [`config::Config`] loaded from `Tardy.toml`.
+/// * The `dispatcher` field holds the [`Dispatcher`] with a handler for each act, see
+///   [`App::dispatch`].
+/// * The `imp_keys` field holds the keymap overlay for windows created by imps, read from the
+///   [`IMP_WINDOW`] table, see [`Lens`].
+/// * The `last_quote` field holds the most recent [`Quote`] vandalized by an imp, which is the
+///   quote rated by [`Act::Upvote`] and [`Act::Downvote`].
+/// * The `ledger` field holds the [`Ledger`] of quote ratings, read from the path in `lore`.
+/// * The `lore` field holds the [`Lore`] describing where the [`ImpKing`] should find quotes.
+/// * The `modifiers` field holds the state of the modifier keys, as of the last
+///   [`WindowEvent::ModifiersChanged`], so key presses can be matched as a [`crate::Chord`].
 /// * The `proxy` fields holds the [`event_loop::EventLoopProxy`] that async processes use to send
 ///   [`Hijinks`] to the main event loop.
+/// * The `ratings` field holds the [`watch::Sender`] that carries the `ledger` to the imps, handed
+///   to the [`ImpKing`] when he is summoned.
+/// * The `recorder` field holds the [`Recorder`] while the user records acts.
+/// * The `registers` field holds the [`Registers`] of finished recordings.
+/// * The `routine` field holds the task playing the current macro or replay, if any, so that a
+///   key press can cancel it, see [`Routine`].
+/// * The `scrawls` field holds the [`Scrawls`] templates used to format quotes from imps.
+/// * The `tempers` field holds the [`Tempers`] handed to the [`ImpKing`] to shape his imps.
 /// * The `windows` field holds a [`HashMap`] with keys of type [`window::WindowId`] and values of type [`Lens`].
 impl App {
     /// Creates an instance of `App`.  Reads user key mappings from `Tardy.toml` using
@@ -74,17 +113,45 @@ impl App {
     /// level data structure, we are using `App` to carry water from `main.rs` to a place where
     /// the async workers can drink it.
     pub fn new(proxy: event_loop::EventLoopProxy<Hijinks>) -> Self {
+        let capture = None;
         let cmd = Cmd::default();
         let config = config::Config::default();
+        let dispatcher = Self::builtins();
+        let imp_keys = None;
+        let last_quote = None;
+        let ledger = Ledger::default();
+        let lore = Lore::default();
+        let modifiers = keyboard::ModifiersState::default();
+        let (ratings, _) = watch::channel(Ledger::default());
+        let recorder = None;
+        let registers = Registers::default();
+        let routine = None;
+        let scrawls = Scrawls::default();
+        let tempers = Tempers::default();
         let windows = HashMap::new();
         let mut app = Self {
+            capture,
             cmd,
             config,
+            dispatcher,
+            imp_keys,
+            last_quote,
+            ledger,
+            lore,
+            modifiers,
             proxy,
+            ratings,
+            recorder,
+            registers,
+            routine,
+            scrawls,
+            tempers,
             windows,
         };
         app.load_config();
         app.load_cmds();
+        app.load_lore();
+        app.load_scrawls();
         app
     }
     /// Instead of using a `WindowBuilder`, we now create a default instance of
@@ -97,8 +164,10 @@ impl App {
     /// Here we wrap the window in an [`Arc`] for no good reason, though I would certainly *like*
     /// to need an [`Arc`] here to render an `egui` menu on top of a GIS map.
     ///
-    /// Finally, we create an instance of [`Lens`] from the window, and insert it as a value into
-    /// the [`HashMap`] in the `windows` field, using the window id as a key.
+    /// Finally, we create an instance of [`Lens`] from the window, show the current mode of the
+    /// keymap in the title using [`Lens::show_mode`], and insert it as a value into the
+    /// [`HashMap`] in the `windows` field, using the window id as a key.  Returns the id, so the
+    /// caller can find the lens again, for instance to give it a keymap overlay.
     ///
     /// Will [`crate::Blame::EventLoop`] when [`event_loop::ActiveEventLoop::create_window`] fails.
     #[tracing::instrument(skip_all)]
@@ -106,7 +175,7 @@ impl App {
         &mut self,
         event_loop: &event_loop::ActiveEventLoop,
         attributes: Option<window::WindowAttributes>,
-    ) -> Arrive<()> {
+    ) -> Arrive<window::WindowId> {
         let attr = if let Some(attributes) = attributes {
             attributes
         } else {
@@ -114,14 +183,17 @@ impl App {
                 .with_title("Tardy")
                 .with_transparent(true)
         };
+        let title = attr.title.clone();
         let window = event_loop.create_window(attr)?;
         let window = Arc::new(window);
         // Did I create a window?
         tracing::trace!("Window created: {:?}", window.id());
-        self.windows.insert(window.id(), Lens::new(window.clone()));
+        let lens = Lens::new(window.clone(), &title);
+        lens.show_mode(self.cmd.mode());
+        self.windows.insert(window.id(), lens);
         // How many am I up to?
         tracing::trace!("Total windows: {}", self.windows.len());
-        Ok(())
+        Ok(window.id())
     }
 
     /// The user specifies key mappings in `Tardy.toml`, as described in the docs for [`Act`].
@@ -133,79 +205,477 @@ impl App {
     /// current `Tardy.toml`.  The current method has some drawbacks.  The default fallback would
     /// get onerous if I had more than two actions to worry about.  Also, I resort to unwrapping
     /// the default build, which will crash my program if it panics for some reason.
+    ///
+    /// The actual reading happens in [`App::read_config`], so that the command line tools can
+    /// read the same configuration without creating an `App`.
     #[tracing::instrument(skip_all)]
     pub fn load_config(&mut self) {
+        self.config = Self::read_config();
+        // Read the config to make sure its correct.
+        tracing::trace!("{:#?}", self.config);
+    }
+
+    /// The `read_config` method reads `Tardy.toml` into a [`config::Config`], falling back on the
+    /// default key mappings if the file cannot be read.  See [`App::load_config`].
+    #[tracing::instrument(skip_all)]
+    pub fn read_config() -> config::Config {
         if let Ok(config) = config::Config::builder()
             .add_source(config::File::with_name("Tardy"))
             .build()
         {
-            self.config = config;
             // Sanity check that the file read correctly.
             tracing::trace!("Config set from file.");
+            config
         } else {
             // Warn me the user config couldn't be read.
             tracing::warn!("Could not read config from file.");
-            let config = config::Config::builder();
-            let config = config.set_default("exit", "Escape").unwrap();
-            let config = config.set_default("new_window", "n").unwrap();
-            let config = config.build().unwrap();
-            self.config = config;
+            let mut config = config::Config::builder();
+            for (act, keys) in DEFAULT_KEYMAP {
+                config = config.set_default(act, keys).unwrap();
+            }
+            config.build().unwrap()
         }
-
-        // Read the config to make sure its correct.
-        tracing::trace!("{:#?}", self.config);
     }
 
     /// Keys and values play reversed roles in the [`Cmd`] and [`config::Config`] structs.  Here we
-    /// convert one to the other using the [`Cmd::from`] implementation.
-    /// Failure to read any commands from the config will produce an empty [`Cmd`], which will
-    /// restrict the user to mouse interactions.
+    /// convert one to the other using the [`Cmd::try_from`] implementation.
+    /// A binding with a bad value, see [`Cmd::is_fatal`], means we report each problem as a
+    /// warning and fall back on the bare [`Preset`] the user picked, or the default keymap if
+    /// they picked none, rather than leave the user with half a keyboard.  Lesser problems earn a warning, and cost only the binding.
+    ///
+    /// The recordings in the [`Registers`] come along too, since they play back through the
+    /// keymap.  Recordings that fail to read earn a warning, and we start with empty registers.
+    ///
+    /// We also read the keymap overlay for imp windows from the [`IMP_WINDOW`] table into the
+    /// `imp_keys` field, using [`Cmd::overlay`].  Problems with the overlay earn a warning, and
+    /// a bad overlay is dropped, leaving imp windows with the global keymap.
     #[tracing::instrument(skip_all)]
     pub fn load_cmds(&mut self) {
-        let cmd = Cmd::from(&self.config);
-        self.cmd = cmd;
+        self.cmd = Self::read_cmd(&self.config);
+        let (overlay, snags) = self.cmd.overlay(&self.config, IMP_WINDOW);
+        self.imp_keys = if snags.is_empty() {
+            overlay
+        } else {
+            for snag in snags {
+                tracing::warn!("Bad binding: {snag}");
+            }
+            tracing::warn!("Ignoring the keymap for imp windows.");
+            None
+        };
+        self.registers = Registers::load(&Registers::path(&self.config)).unwrap_or_else(|e| {
+            tracing::warn!("Could not read recordings: {e}");
+            Registers::default()
+        });
         tracing::trace!("Commands read from config.");
         // Do you see the commands you expected?
         tracing::trace!("{:?}", self.cmd);
     }
 
-    /// The act method dispatches program responses based upon the variant of [`Act`] passed in the
-    /// `act` argument. Takes a mutable reference to `Self` in order to create and remove windows
+    /// The `read_cmd` method reads the keymap from `config`, reporting bad bindings and falling
+    /// back on the chosen preset as described in [`App::load_cmds`].  Split out so that
+    /// `tardy keys` can read the same keymap without creating an `App`.
+    pub fn read_cmd(config: &config::Config) -> Cmd {
+        match Cmd::try_from(config) {
+            Ok(cmd) => cmd,
+            Err(snags) => {
+                for snag in snags {
+                    tracing::warn!("Bad binding: {snag}");
+                }
+                let preset = Preset::from_config(config);
+                tracing::warn!("Falling back on the {} keymap.", preset.snake());
+                Cmd::preset(preset)
+            }
+        }
+    }
+
+    /// The `load_lore` method reads the `[quotes]` table from the config into the `lore` field
+    /// using the [`Lore::from`] implementation.  A missing table is not a problem, it just means
+    /// the imps will be quoting from the built-in corpus.  While we are at it, we read the
+    /// `[[imps]]` tables into the `tempers` field using the [`Tempers::from`] implementation, and
+    /// the [`Ledger`] of quote ratings into the `ledger` field.  A ledger that fails to read earns
+    /// a warning, and we start over with an empty one.  Either way, the ledger goes out on the
+    /// `ratings` channel, so the imps start from the same ratings as the application.
+    #[tracing::instrument(skip_all)]
+    pub fn load_lore(&mut self) {
+        self.lore = Lore::from(&self.config);
+        tracing::trace!("{:?}", self.lore);
+        self.ledger = Ledger::load(self.lore.ratings()).unwrap_or_else(|e| {
+            tracing::warn!("Could not read ratings: {e}");
+            Ledger::default()
+        });
+        self.ratings.send_replace(self.ledger.clone());
+        self.tempers = Tempers::from(&self.config);
+        tracing::trace!("{:?}", self.tempers);
+    }
+
+    /// The `load_scrawls` method reads message templates from the `[vandalize]` table of the
+    /// config into the `scrawls` field using [`Scrawls::from_config`].  Templates with unknown
+    /// placeholders are rejected here, with a warning for each, so the user hears about the typo
+    /// at startup instead of squinting at a garbled message later.
+    #[tracing::instrument(skip_all)]
+    pub fn load_scrawls(&mut self) {
+        let (scrawls, problems) = Scrawls::from_config(&self.config);
+        for problem in problems {
+            tracing::warn!("Rejected template: {problem}");
+        }
+        self.scrawls = scrawls;
+        tracing::trace!("{:?}", self.scrawls);
+    }
+
+    /// The act method dispatches the program response to the [`Act`] passed in the `act`
+    /// argument. Takes a mutable reference to `Self` in order to create and remove windows
     /// from the `windows` field.  The `id` parameter identifies the window upon which to apply the
     /// action.  The `event_loop` provides a reference to the active event loop for new window
     /// creation.
     ///
-    /// We match on `act` and dispatch to the appropriate handler, before returning `Ok`.
+    /// We used to match on `act` right here.  Now we wrap `act` in a [`Deed`] from
+    /// [`Origin::User`] and pass it to [`App::dispatch`], the same path taken by the acts of
+    /// routines and imps, see [`Dispatcher`].
     /// Will [`crate::Blame::EventLoop`] if [`App::create_window`] fails.
-    #[tracing::instrument(skip_all)]
     pub fn act(
         &mut self,
         act: &Act,
         id: &window::WindowId,
         event_loop: &event_loop::ActiveEventLoop,
     ) -> Arrive<()> {
-        match act {
-            Act::CloseWindow => {
-                tracing::trace!("Closing window.");
-                let _ = self.windows.remove(id);
-                Ok(())
+        let deed = Deed::new(act.clone(), Origin::User, Some(*id));
+        self.dispatch(&deed, event_loop)
+    }
+
+    /// The `dispatch` method performs `deed` by calling the [`crate::Handler`] registered under
+    /// the [`Act::name`] of its act in the `dispatcher` field.  While the user records, each act
+    /// passes through the [`Recorder`] on the way in, unless an imp sent it, since the user did
+    /// not ask for that.  A failed handler earns a warning here before the error goes back to
+    /// the caller, so acts from every source report failures the same way.
+    #[tracing::instrument(skip_all)]
+    pub fn dispatch(
+        &mut self,
+        deed: &Deed,
+        event_loop: &event_loop::ActiveEventLoop,
+    ) -> Arrive<()> {
+        let name = deed.act().name();
+        tracing::trace!("Dispatching {name} from {}.", deed.origin());
+        if !deed.origin().is_imp() {
+            if let Some(recorder) = self.recorder.as_mut() {
+                recorder.record(deed.act());
             }
-            Act::Exit => {
-                tracing::trace!("Requesting exit.");
-                self.windows.clear();
-                Ok(())
+        }
+        let Some(handler) = self.dispatcher.get(&name) else {
+            tracing::warn!("No handler for {name}.");
+            return Ok(());
+        };
+        handler(self, deed, event_loop)
+            .inspect_err(|e| tracing::warn!("Failed to {name} for {}: {e}", deed.origin()))
+    }
+
+    /// The `register` method registers `handler` to answer the acts called `name`, see
+    /// [`Dispatcher::register`].  Registering the name of a built-in act replaces the built-in
+    /// handler.
+    pub fn register<F>(&mut self, name: &str, handler: F)
+    where
+        F: Fn(&mut App, &Deed, &event_loop::ActiveEventLoop) -> Arrive<()> + Send + Sync + 'static,
+    {
+        self.dispatcher.register(name, handler);
+    }
+
+    /// The `builtins` method returns a [`Dispatcher`] with a handler for each built-in act.
+    /// [`Act::Custom`] is left to the library user.
+    fn builtins() -> Dispatcher {
+        let mut dispatcher = Dispatcher::default();
+        for act in Act::iter() {
+            let handler: fn(&mut Self, &Deed, &event_loop::ActiveEventLoop) -> Arrive<()> =
+                match act {
+                    Act::CloseWindow => Self::close_window,
+                    Act::Exit => Self::exit,
+                    Act::NewWindow { .. } => Self::new_window,
+                    Act::ResizeWindow { .. } => Self::resize_window,
+                    Act::Upvote => |app, _, _| app.rate(1),
+                    Act::Downvote => |app, _, _| app.rate(-1),
+                    Act::SwitchMode { .. } => Self::switch_mode,
+                    Act::Rebind => Self::rebind,
+                    Act::ShowHelp => Self::show_help,
+                    Act::Macro { .. } => Self::play_macro,
+                    Act::Record => Self::record,
+                    Act::StopRecording { .. } => Self::stop_recording,
+                    Act::Replay { .. } => Self::replay,
+                    Act::Custom { .. } => continue,
+                    Act::Be => Self::be,
+                };
+            dispatcher.register(&act.name(), handler);
+        }
+        dispatcher
+    }
+
+    /// The `close_window` method answers [`Act::CloseWindow`] by closing the window aimed at.
+    /// An imp aims at no window in particular, so we close one at random, unless it is the last.
+    fn close_window(&mut self, deed: &Deed, _: &event_loop::ActiveEventLoop) -> Arrive<()> {
+        if let Some(id) = deed.window() {
+            tracing::trace!("Closing window.");
+            let _ = self.windows.remove(id);
+            return Ok(());
+        }
+        let keys = self
+            .windows
+            .keys()
+            .cloned()
+            .collect::<Vec<window::WindowId>>();
+        if keys.len() > 1 {
+            let mut rng = rand::thread_rng();
+            let idx = rng.gen_range(0..keys.len());
+            self.windows.remove(&keys[idx]);
+        } else {
+            tracing::trace!("App refuses to close the last window.");
+        }
+        Ok(())
+    }
+
+    /// The `exit` method answers [`Act::Exit`] by closing every window.
+    fn exit(&mut self, _: &Deed, _: &event_loop::ActiveEventLoop) -> Arrive<()> {
+        tracing::trace!("Requesting exit.");
+        self.windows.clear();
+        Ok(())
+    }
+
+    /// The `new_window` method answers [`Act::NewWindow`] by opening `count` windows, or one
+    /// window in the frame chosen by an imp, see [`App::imp_window`].
+    fn new_window(&mut self, deed: &Deed, event_loop: &event_loop::ActiveEventLoop) -> Arrive<()> {
+        if let Origin::Imp(meddle) = deed.origin() {
+            return self.imp_window(meddle, event_loop);
+        }
+        let Act::NewWindow { count } = deed.act() else {
+            return Ok(());
+        };
+        for _ in 0..count.unwrap_or(1) {
+            self.create_window(event_loop, None)?;
+        }
+        Ok(())
+    }
+
+    /// The `imp_window` method opens a window in the frame of `meddle`, titled after the imp,
+    /// with the keymap overlay from the [`IMP_WINDOW`] table.
+    ///
+    /// Will [`crate::Blame::EventLoop`] if [`App::create_window`] fails.
+    pub fn imp_window(
+        &mut self,
+        meddle: &Meddle,
+        event_loop: &event_loop::ActiveEventLoop,
+    ) -> Arrive<()> {
+        let Some(frame) = meddle.frame() else {
+            tracing::warn!("New window invocations should always include a frame.");
+            return Ok(());
+        };
+        tracing::trace!("Creating window from imp.");
+        let attr = window::Window::default_attributes()
+            .with_title(meddle.title())
+            .with_transparent(true)
+            .with_position(*frame.position())
+            .with_inner_size(*frame.size());
+        let id = self.create_window(event_loop, Some(attr))?;
+        if let Some(lens) = self.windows.get_mut(&id) {
+            lens.with_keys(self.imp_keys.clone());
+        }
+        Ok(())
+    }
+
+    /// The `resize_window` method answers [`Act::ResizeWindow`] by asking the platform to resize
+    /// the window aimed at.
+    fn resize_window(&mut self, deed: &Deed, _: &event_loop::ActiveEventLoop) -> Arrive<()> {
+        let Act::ResizeWindow { width, height } = deed.act() else {
+            return Ok(());
+        };
+        if let Some(lens) = deed.window().as_ref().and_then(|id| self.windows.get(id)) {
+            let size = dpi::PhysicalSize::new(*width, *height);
+            // The platform may resize the window later, or not at all.
+            let _ = lens.window().request_inner_size(size);
+            tracing::trace!("Resize requested: {width}x{height}");
+        }
+        Ok(())
+    }
+
+    /// The `switch_mode` method answers [`Act::SwitchMode`] by switching the keymap to the mode,
+    /// see [`Cmd::switch`].
+    fn switch_mode(&mut self, deed: &Deed, _: &event_loop::ActiveEventLoop) -> Arrive<()> {
+        if let Act::SwitchMode { mode } = deed.act() {
+            self.cmd.switch(mode)?;
+            self.show_mode();
+        }
+        Ok(())
+    }
+
+    /// The `rebind` method answers [`Act::Rebind`] by starting a [`Capture`].
+    fn rebind(&mut self, _: &Deed, _: &event_loop::ActiveEventLoop) -> Arrive<()> {
+        let capture = Capture::new();
+        tracing::info!("Pick an act to rebind with Tab, then press Enter.");
+        self.capture = Some(capture);
+        self.show_mode();
+        Ok(())
+    }
+
+    /// The `show_help` method answers [`Act::ShowHelp`] by logging a cheat sheet.
+    fn show_help(&mut self, _: &Deed, _: &event_loop::ActiveEventLoop) -> Arrive<()> {
+        tracing::info!("Key bindings:\n{}", Sheet::Text.render(&self.cmd));
+        Ok(())
+    }
+
+    /// The `play_macro` method answers [`Act::Macro`] by playing the macro on the window aimed
+    /// at.
+    fn play_macro(&mut self, deed: &Deed, _: &event_loop::ActiveEventLoop) -> Arrive<()> {
+        let (Act::Macro { name }, Some(id)) = (deed.act(), deed.window()) else {
+            return Ok(());
+        };
+        let Some(routine) = self.cmd.macros().get(name).cloned() else {
+            tracing::warn!("No macro named {name}.");
+            return Ok(());
+        };
+        self.play(&format!("macro {name}"), routine, id);
+        Ok(())
+    }
+
+    /// The `record` method answers [`Act::Record`] by starting a [`Recorder`].  If a recording
+    /// is already under way, we keep it rather than throw away the acts recorded so far.
+    fn record(&mut self, _: &Deed, _: &event_loop::ActiveEventLoop) -> Arrive<()> {
+        if self.recorder.is_some() {
+            tracing::warn!("Already recording.");
+            return Ok(());
+        }
+        tracing::info!("Recording.");
+        self.recorder = Some(Recorder::new());
+        self.show_mode();
+        Ok(())
+    }
+
+    /// The `stop_recording` method answers [`Act::StopRecording`] by keeping the recording in
+    /// the register, then saving the [`Registers`].
+    ///
+    /// Will [`crate::Blame::Io`] or [`crate::Blame::Json`] if the registers fail to save.
+    fn stop_recording(&mut self, deed: &Deed, _: &event_loop::ActiveEventLoop) -> Arrive<()> {
+        let Act::StopRecording { register } = deed.act() else {
+            return Ok(());
+        };
+        let Some(recorder) = self.recorder.take() else {
+            tracing::info!("Not recording.");
+            return Ok(());
+        };
+        let name = Registers::name(register.as_deref());
+        let routine = recorder.finish();
+        tracing::info!("Recorded {} steps to register {name}.", routine.len());
+        self.registers.insert(name.to_string(), routine);
+        self.show_mode();
+        self.registers.save(&Registers::path(&self.config))
+    }
+
+    /// The `replay` method answers [`Act::Replay`] by playing the recording in the register on
+    /// the window aimed at.
+    fn replay(&mut self, deed: &Deed, _: &event_loop::ActiveEventLoop) -> Arrive<()> {
+        let (Act::Replay { register }, Some(id)) = (deed.act(), deed.window()) else {
+            return Ok(());
+        };
+        let name = Registers::name(register.as_deref());
+        let Some(routine) = self.registers.get(name).cloned() else {
+            tracing::info!("Nothing recorded in register {name}.");
+            return Ok(());
+        };
+        self.play(&format!("register {name}"), routine, id);
+        Ok(())
+    }
+
+    /// The `be` method answers [`Act::Be`] by taking it easy.
+    fn be(&mut self, _: &Deed, _: &event_loop::ActiveEventLoop) -> Arrive<()> {
+        tracing::trace!("Taking it easy.");
+        Ok(())
+    }
+
+    /// The `play` method plays `routine` as a background task, aimed at the window `id`, see
+    /// [`Routine::play`].  Any routine already playing stops first, so routines never pile up.
+    /// The `name` describes the routine in the logs.
+    fn play(&mut self, name: &str, routine: Routine, id: &window::WindowId) {
+        self.stop_routine();
+        tracing::info!("Playing {name}.");
+        let play = routine.play(*id, self.proxy.clone());
+        let name = name.to_string();
+        self.routine = Some(tokio::spawn(async move {
+            if let Err(e) = play.await {
+                tracing::warn!("Stopped playing {name}: {e}");
             }
-            Act::NewWindow => self.create_window(event_loop, None),
-            Act::Be => {
-                tracing::trace!("Taking it easy.");
-                Ok(())
+        }));
+    }
+
+    /// The `stop_routine` method cancels the macro playing in the `routine` field, if it is still
+    /// playing.  Returns `true` if there was a macro to cancel.
+    fn stop_routine(&mut self) -> bool {
+        match self.routine.take() {
+            Some(routine) if !routine.is_finished() => {
+                routine.abort();
+                tracing::info!("Macro cancelled.");
+                true
             }
+            _ => false,
+        }
+    }
+
+    /// The `show_mode` method shows the state of the keymap in the title of each window, using
+    /// [`Lens::show_mode`].  During a [`Capture`], the title shows the progress of the capture in
+    /// place of the mode.  While recording, the title says so.
+    fn show_mode(&self) {
+        let mut tag = match &self.capture {
+            Some(capture) => capture.to_string(),
+            None => self.cmd.mode().clone(),
+        };
+        if self.recorder.is_some() {
+            tag.push_str(", recording");
+        }
+        self.windows.values().for_each(|lens| lens.show_mode(&tag));
+    }
+
+    /// The `recapture` method feeds `chord` to the [`Capture`] in progress.  When the capture
+    /// hands back a binding, we apply it with [`Cmd::rebind`] and save it with [`Capture::save`].
+    /// A conflict with another binding earns a warning, and the capture waits for another
+    /// chord.  A failure to save earns a warning too, but the new binding holds for the session.
+    fn recapture(&mut self, chord: &Chord) {
+        let Some(capture) = self.capture.as_mut() else {
+            return;
+        };
+        match capture.feed(chord) {
+            Step::Wait => {}
+            Step::Cancel => self.capture = None,
+            Step::Bind(act, keys) => match self.cmd.rebind(&act, &keys) {
+                Ok(()) => {
+                    self.capture = None;
+                    let path = std::path::Path::new(CONFIG_PATH);
+                    if let Err(e) = Capture::save(path, &act, &[keys]) {
+                        tracing::warn!("Could not save binding: {e}");
+                    }
+                }
+                Err(e) => tracing::warn!("{e}, try another key."),
+            },
         }
+        self.show_mode();
+    }
+
+    /// The `rate` method adds `delta` to the rating of the most recent quote in the `last_quote`
+    /// field using [`Ledger::rate`], then saves the ledger so the rating survives the session.
+    /// We send the ledger down the `ratings` channel first, so the imps hear about the vote right
+    /// away, whether or not the [`ImpKing`] is watching files.  If no imp has spoken yet, there is
+    /// nothing to rate.
+    ///
+    /// Will [`crate::Blame::Io`] or [`crate::Blame::Json`] if the ledger fails to save.
+    #[tracing::instrument(skip_all)]
+    pub fn rate(&mut self, delta: i32) -> Arrive<()> {
+        let Some(quote) = &self.last_quote else {
+            tracing::info!("No quote to rate yet.");
+            return Ok(());
+        };
+        let rating = self.ledger.rate(quote, delta);
+        self.ratings.send_replace(self.ledger.clone());
+        self.ledger.save(self.lore.ratings())?;
+        tracing::info!("Rated {} at {rating}.", quote.graffiti());
+        Ok(())
     }
 
     /// The `keyboard_input` method takes incoming keyboard presses and translates them to an [`Act`] variant using the [`Cmd::act`] method.
     /// If the key event passed in the `event` argument translates to a valid [`Act`], we pass it
-    /// to the [`App::act`] method for handling.
+    /// to the [`App::act`] method for handling.  While a [`Capture`] is in progress, key presses
+    /// go to the capture instead, see [`Capture::feed`].
     ///
     /// Takes a mutable reference to `self` to pass to [`App::act`].
     /// The `id` parameter indicates the active window and gets passed to [`App::act`].
@@ -220,10 +690,20 @@ impl App {
         event_loop: &event_loop::ActiveEventLoop,
     ) -> Arrive<()> {
         // Dispatch actions only on press.
-        if event.state.is_pressed() {
+        if event.state.is_pressed() && self.capture.is_some() {
+            // A capture takes every key but the modifiers, which belong to the chord.
+            if !Chord::is_modifier(event) {
+                if let Some(chord) = Chord::from_event(event, self.modifiers) {
+                    self.recapture(&chord);
+                }
+            }
+        } else if event.state.is_pressed() && !Chord::is_modifier(event) && self.stop_routine() {
+            // The key that stops a macro does nothing else.
+        } else if event.state.is_pressed() {
             // Tell me I at least pressed the right key.
             tracing::trace!("Press detected: {:#?}", event);
-            if let Some(act) = self.cmd.act(event) {
+            let mods = self.modifiers;
+            if let Some(act) = self.lookup(id, |cmd| cmd.act(event, mods)) {
                 // Helpful to know it triggered if the handler doesn't respond right.
                 tracing::trace!("Act detected: {act}");
                 self.act(&act, id, event_loop)?;
@@ -235,6 +715,58 @@ impl App {
         Ok(())
     }
 
+    /// The `lookup` method finds the [`Act`] for an input to the window `id`, calling `find` on
+    /// the keymap overlay of the window first, if it has one, then on the global keymap in the
+    /// `cmd` field, see [`Cmd::lookup`].
+    fn lookup<F>(&mut self, id: &window::WindowId, find: F) -> Option<Act>
+    where
+        F: FnMut(&mut Cmd) -> Option<Act>,
+    {
+        let overlay = self.windows.get_mut(id).and_then(|lens| lens.keys_mut());
+        self.cmd.lookup(overlay, find)
+    }
+
+    /// The `mouse_input` method does for mouse buttons what [`App::keyboard_input`] does for keys,
+    /// translating presses of the `button` to an [`Act`] using [`Cmd::click`], and passing the
+    /// result to [`App::act`].  Releasing the button does nothing.
+    #[tracing::instrument(skip_all)]
+    pub fn mouse_input(
+        &mut self,
+        id: &window::WindowId,
+        state: event::ElementState,
+        button: event::MouseButton,
+        event_loop: &event_loop::ActiveEventLoop,
+    ) -> Arrive<()> {
+        if state.is_pressed() && self.stop_routine() {
+            // The click that stops a macro does nothing else.
+        } else if state.is_pressed() {
+            tracing::trace!("Click detected: {button:?}");
+            let mods = self.modifiers;
+            if let Some(act) = self.lookup(id, |cmd| cmd.click(button, mods)) {
+                tracing::trace!("Act detected: {act}");
+                self.act(&act, id, event_loop)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The `mouse_wheel` method translates turns of the mouse wheel to an [`Act`] using
+    /// [`Cmd::scroll`], and passes the result to [`App::act`].
+    #[tracing::instrument(skip_all)]
+    pub fn mouse_wheel(
+        &mut self,
+        id: &window::WindowId,
+        delta: event::MouseScrollDelta,
+        event_loop: &event_loop::ActiveEventLoop,
+    ) -> Arrive<()> {
+        let mods = self.modifiers;
+        if let Some(act) = self.lookup(id, |cmd| cmd.scroll(delta, mods)) {
+            tracing::trace!("Act detected: {act}");
+            self.act(&act, id, event_loop)?;
+        }
+        Ok(())
+    }
+
     /// The `screen_sizes` method returns a vector of physical sizes for each monitor available to
     /// the app.  The purpose of this function is to ascertain valid areas for drawing new windows.
     /// We avoid asking for windows with areas outside the size of the target screen.
@@ -453,9 +985,13 @@ impl App {
     #[tracing::instrument(skip_all)]
     pub fn imp_king(&mut self) {
         let proxy = self.proxy.clone();
+        let lore = self.lore.clone();
+        let tempers = self.tempers.clone();
+        let ratings = self.ratings.clone();
         if let Some(frames) = self.frames(FRAME_POOL) {
             tokio::spawn(async move {
-                let mut king = ImpKing::summon(proxy, FRAMES, frames).unwrap();
+                let mut king =
+                    ImpKing::summon(proxy, FRAMES, frames, &lore, &tempers, ratings).unwrap();
                 if king.reign(IMPS).await.is_err() {
                     tracing::warn!("Problem making hijinks.");
                 }
@@ -471,7 +1007,10 @@ impl App {
 ///   to make the initial window.  Calls [`App::create_window`] and unwraps it with an `expect`.
 /// * The `window_event` method removes the current window on a [`WindowEvent::CloseRequested`].
 ///   It dispatches keyboard input from a [`WindowEvent::KeyboardInput`] to the [`App::keyboard_input`]
-///   method, converting errors to trace level logs (hopefully they weren't important).
+///   method, converting errors to trace level logs (hopefully they weren't important).  It
+///   also keeps track of the modifier keys from [`WindowEvent::ModifiersChanged`], and
+///   dispatches [`WindowEvent::MouseInput`] and [`WindowEvent::MouseWheel`] to
+///   [`App::mouse_input`] and [`App::mouse_wheel`] in the same way.
 /// * The [`WindowEvent::RedrawRequested`] variant will trigger a [`window::Window::request_redraw`]
 ///   call if the `refresh` field on [`Lens`] is set to `true`, which it never is.
 /// * We delegate program exit to the `about_to_wait` method, where we check to see if there are open
@@ -497,7 +1036,7 @@ impl App {
 ///   The example code from the [`event_loop::EventLoop::create_proxy`] method includes an
 ///   interesting tidbit:
 ///
-///   ```
+///   ```ignore
 ///   let event_loop = EventLoop::<UserEvent>::with_user_event().build()?;
 ///   ```
 ///
@@ -506,7 +1045,7 @@ impl App {
 ///   `UserEvent`. We have amended our code in `main.rs` to include the [`Hijinks`] event.
 ///   We proceed to create a proxy, as in the example code:
 ///
-///   ```
+///   ```ignore
 ///   let event_loop = event_loop::EventLoop::<Hijinks>::with_user_event().build()?;
 ///   let proxy = event_loop.create_proxy();
 ///   ```
@@ -518,13 +1057,18 @@ impl App {
 ///
 ///   We match on the variant of [`Hijinks`] to determine program response:
 ///
-///   * [`Hijinks::Meddle`] indicates a proxy action and contains an [`Act`] variant.
-///     We match on the [`Act`] variant to determine response:
-///     *  [`Act::CloseWindow`] - Respond by closing a random window, without regard to "owner".
-///     *  [`Act::NewWindow`] - Respond by opening a new window. Contains a [`Frame`] specifying
-///        position and window size.
-///     * No further variants of [`Act`] participate in [`Hijinks`].
-///   * [`Hijinks::Vandalize`] - Respond by logging the contained message as an INFO level trace.
+///   * [`Hijinks::Meddle`] indicates a proxy action and contains an [`Act`] variant.  We wrap the
+///     act in a [`Deed`] with an [`Origin::Imp`] holding the meddle, aimed at no window in
+///     particular, and hand it to [`App::dispatch`], same as an act bound to a key.  The handlers
+///     look at the origin where it matters, so [`Act::NewWindow`] opens the window in the
+///     [`Frame`] the imp chose, see [`App::imp_window`], and the [`crate::Recorder`] leaves the
+///     acts of imps out of a recording.
+///   * [`Hijinks::Cue`] carries a step of a macro or replay, see [`crate::Routine::play`].  We wrap
+///     the act in a [`Deed`] with [`Origin::Routine`], aimed at the window the routine started
+///     in, and hand it to [`App::dispatch`] as well.
+///   * [`Hijinks::Vandalize`] - Respond by formatting the contained [`crate::Graffiti`] using
+///     [`Scrawls::render`], and logging the message as an INFO level trace.  We hold on to the
+///     quote, in case the user wants to rate it, and write it down in the [`Journal`].
 ///   * [`Hijinks::Filch`] - Respond by sending a vector of [`Frame`] instances to the filcher.
 ///
 ///   As a parting sad trombone, I have not been able to figure out how to use the
@@ -543,40 +1087,23 @@ impl ApplicationHandler<Hijinks> for App {
     fn user_event(&mut self, event_loop: &event_loop::ActiveEventLoop, event: Hijinks) {
         tracing::trace!("Hijinks detected.");
         match event {
-            Hijinks::Meddle(meddle) => match meddle.act() {
-                Act::CloseWindow => {
-                    tracing::trace!("Close window received.");
-                    let keys = self
-                        .windows
-                        .keys()
-                        .cloned()
-                        .collect::<Vec<window::WindowId>>();
-                    if keys.len() > 1 {
-                        let mut rng = rand::thread_rng();
-                        let idx = rng.gen_range(0..keys.len());
-                        self.windows.remove(&keys[idx]);
-                    } else {
-                        tracing::trace!("App refuses to close the last window.");
-                    }
-                }
-                Act::NewWindow => {
-                    if let Some(frame) = meddle.frame() {
-                        tracing::trace!("Creating window from imp.");
-                        let position = frame.position();
-                        let size = frame.size();
-                        let attr = window::Window::default_attributes()
-                            .with_title(meddle.title())
-                            .with_transparent(true)
-                            .with_position(*position)
-                            .with_inner_size(*size);
-                        self.create_window(event_loop, Some(attr)).unwrap();
-                    } else {
-                        tracing::warn!("New window invocations should always include a frame.");
-                    }
+            Hijinks::Meddle(meddle) => {
+                let deed = Deed::new(meddle.act().clone(), Origin::Imp(meddle), None);
+                // The dispatcher reports any failure.
+                let _ = self.dispatch(&deed, event_loop);
+            }
+            Hijinks::Vandalize(graffiti) => {
+                let msg = self.scrawls.render(&graffiti, self.windows.len());
+                tracing::info!(msg);
+                if let Err(e) = Journal::record(self.lore.journal(), &Entry::from(&graffiti)) {
+                    tracing::warn!("Could not record quote in journal: {e}");
                 }
-                _ => tracing::warn!("Imps can't send this type of act."),
-            },
-            Hijinks::Vandalize(msg) => tracing::info!(msg),
+                self.last_quote = Some(graffiti.quote().clone());
+            }
+            Hijinks::Cue(id, act) => {
+                let deed = Deed::new(act, Origin::Routine, Some(id));
+                let _ = self.dispatch(&deed, event_loop);
+            }
             Hijinks::Filch(filch) => {
                 if let Some(frames) = self.frames(FRAMES) {
                     let tx = filch.dissolve();
@@ -604,6 +1131,10 @@ impl ApplicationHandler<Hijinks> for App {
                 self.windows.remove(&id);
                 tracing::trace!("Windows remaining: {}", self.windows.len());
             }
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers.state();
+                tracing::trace!("Modifiers: {:?}", self.modifiers);
+            }
             WindowEvent::KeyboardInput {
                 event,
                 is_synthetic: false,
@@ -614,7 +1145,19 @@ impl ApplicationHandler<Hijinks> for App {
                     Err(e) => tracing::trace!("Unexpected: {}", e.to_string()),
                 };
             }
-            WindowEvent::RedrawRequested => {
+            WindowEvent::MouseInput { state, button, .. } => {
+                match self.mouse_input(&id, state, button, event_loop) {
+                    Ok(_) => tracing::trace!("Event handled."),
+                    Err(e) => tracing::trace!("Unexpected: {}", e.to_string()),
+                };
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                match self.mouse_wheel(&id, delta, event_loop) {
+                    Ok(_) => tracing::trace!("Event handled."),
+                    Err(e) => tracing::trace!("Unexpected: {}", e.to_string()),
+                };
+            }
+            WindowEvent::RedrawRequested if *window.refresh() => {
                 // I left these comments in from the example to remind me to put some cool stuff
                 // here later.
                 //
@@ -631,10 +1174,8 @@ impl ApplicationHandler<Hijinks> for App {
                 // You only need to call this if you've determined that you need to redraw in
                 // applications which do not always need to. Applications that redraw continuously
                 // can render here instead.
-                if *window.refresh() {
-                    window.window().request_redraw();
-                    window.with_refresh(false);
-                }
+                window.window().request_redraw();
+                window.with_refresh(false);
             }
             _ => (),
         }
@@ -714,6 +1255,10 @@ pub const FRAME_POOL: usize = 100;
 /// The `FRAMES` constant determines the number of frames given to each [`crate::Imp`].
 pub const FRAMES: usize = 10;
 
+/// The `IMP_WINDOW` constant names the table in `Tardy.toml` holding the keymap overlay for
+/// windows created by imps, see [`App::load_cmds`].
+pub const IMP_WINDOW: &str = "imp_window";
+
 /// The `IMPS` constant specifies the number of [`crate::Imp`] instances created by [`ImpKing::spawn_imps`]
 pub const IMPS: usize = 10;
 
@@ -721,3 +1266,29 @@ pub const IMPS: usize = 10;
 /// new windows, as well as the minimum padding between window and screen sizes.
 /// Used to implement [`From<monitor::MonitorHandle>`] for [`Frame`].
 pub const MIN_SPAN: u32 = 50;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(src: &str) -> config::Config {
+        config::Config::builder()
+            .add_source(config::File::from_str(src, config::FileFormat::Toml))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn bad_keymap_falls_back_on_preset() {
+        let mut cmd = App::read_cmd(&config("keymap = \"vim\"\nexit = 1"));
+        assert_eq!(cmd.press("Escape".parse().unwrap()), None);
+        assert_eq!(cmd.press(":".parse().unwrap()), None);
+        assert_eq!(cmd.press("q".parse().unwrap()), Some(Act::Exit));
+    }
+
+    #[test]
+    fn bad_keymap_without_preset_falls_back_on_default() {
+        let mut cmd = App::read_cmd(&config("exit = 1"));
+        assert_eq!(cmd.press("Escape".parse().unwrap()), Some(Act::Exit));
+    }
+}
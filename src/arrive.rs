@@ -22,7 +22,6 @@ use derive_more::Error;
 /// variants for different internal error conditions.
 #[derive(
     Debug,
-    Copy,
     Clone,
     PartialEq,
     Eq,
@@ -39,6 +38,10 @@ pub enum Blame {
     #[from(csv::Error)]
     #[display("Csv: {:?}", self.source())]
     Csv,
+    /// The `Config` variant indicates the [`config`] crate could not make sense of `Tardy.toml`.
+    #[from(config::ConfigError)]
+    #[display("Config: {:?}", self.source())]
+    Config,
     /// The `EventLoop` variant triggers on failure to create a new [`winit`] event loop.
     #[from(winit::error::EventLoopError)]
     #[display("EventLoop: {:?}", self.source())]
@@ -56,6 +59,11 @@ pub enum Blame {
     #[from(std::io::Error)]
     #[display("Io: {:?}", self.source())]
     Io,
+    /// The `Json` variant indicates the [`serde_json`] crate could not parse a JSON file of
+    /// quotes.
+    #[from(serde_json::Error)]
+    #[display("Json: {:?}", self.source())]
+    Json,
     /// The `Oneshot` variant indicates an error in the [`tokio`] oneshot channel used to call for
     /// [`crate::Frame`] instances from the [`crate::App`] and receive new frames.
     #[from(tokio::sync::oneshot::error::RecvError)]
@@ -65,11 +73,25 @@ pub enum Blame {
     #[from(winit::error::OsError)]
     #[display("OsError: {:?}", self.source())]
     OsError,
+    /// The `Toml` variant indicates the [`toml`] crate could not parse a TOML file of quotes.
+    #[from(toml::de::Error)]
+    #[display("Toml: {:?}", self.source())]
+    Toml,
+    /// The `TomlEdit` variant indicates the [`toml_edit`] crate could not parse `Tardy.toml` when
+    /// writing back a new binding, see [`crate::Capture::save`].
+    #[from(toml_edit::TomlError)]
+    #[display("TomlEdit: {:?}", self.source())]
+    TomlEdit,
     /// The `Tokio` variant indicates an error with the mpsc channel used to send [`Hijinks`] from
     /// [`crate::Imp`] types to the [`crate::ImpKing`].
     #[from(tokio::sync::mpsc::error::SendError<Hijinks>)]
     #[display("Tokio: {:?}", self.source())]
     Tokio,
+    /// The `Yaml` variant indicates the [`serde_yaml`] crate could not parse a YAML file of
+    /// quotes.
+    #[from(serde_yaml::Error)]
+    #[display("Yaml: {:?}", self.source())]
+    Yaml,
 }
 
 /// The `Arrive` type is an alias of the [`Result`] type, using the common error type [`Blame`].
@@ -78,22 +100,74 @@ pub enum Blame {
 pub type Arrive<T> = Result<T, Blame>;
 
 /// The `Excuse` enum describes internal library error states.
-/// Currently the only variant is `NoFrames`, indicating the struct does not have a frame to pop
-/// from the `frames` field.
+///
+/// The `Excuse` enum and the [`Blame`] enum used to be [`Copy`], back when no excuse had any
+/// details to offer.  Now that a bad template needs to say which placeholder it did not
+/// recognize, the variants carry data and we settle for [`Clone`].
 #[derive(
-    Debug,
-    Copy,
-    Clone,
-    PartialEq,
-    Eq,
-    PartialOrd,
-    Ord,
-    Hash,
-    derive_more::Error,
-    derive_more::Display,
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, derive_more::Error, derive_more::Display,
 )]
 pub enum Excuse {
+    /// The `BadRow` variant indicates a quote in a file failed to read, where a single bad row
+    /// spoils the file, see [`crate::Quotes::from_path_strict`].  The variant holds the path, the
+    /// row and the problem.
+    #[display("Bad quote in {path} on row {row}: {problem}")]
+    BadRow {
+        path: String,
+        row: usize,
+        problem: String,
+    },
+    /// The `Binding` variant indicates a key in `Tardy.toml` has a value that cannot be a key
+    /// binding, like a number, or text that does not parse as a [`crate::Sequence`].
+    #[display("Invalid binding: {key} = {value}")]
+    Binding { key: String, value: String },
+    /// The `Chord` variant indicates a key binding could not be read as a [`crate::Chord`].  The
+    /// variant holds the offending binding.
+    #[display("Invalid key chord: {_0}")]
+    Chord(#[error(not(source))] String),
+    /// The `Conflict` variant indicates a [`crate::Chord`] is bound more than once in
+    /// `Tardy.toml`.  The variant holds the chord and the acts competing for it.
+    #[display("Key bound more than once: {_0}")]
+    Conflict(#[error(not(source))] String),
+    /// The `Flawed` variant indicates an [`crate::Audit`] found problems with a file of quotes.
+    /// The variant holds the number of findings.
+    #[display("Found {_0} problems with the quotes")]
+    Flawed(#[error(not(source))] usize),
     /// The `NoFrames` variant indicates the struct does not have a frame to pop from the
     /// `frames` field.
     NoFrames,
+    /// The `NoQuotes` variant indicates a source of quotes did not contain any quotes.
+    NoQuotes,
+    /// The `Parameter` variant indicates an [`crate::Act`] has a parameter out of bounds, like a
+    /// window too small to see.  The variant holds the act and the offending parameter.
+    #[display("Invalid parameter: {_0}")]
+    Parameter(#[error(not(source))] String),
+    /// The `Placeholder` variant indicates a template contains a placeholder we do not know how
+    /// to fill, see [`crate::Scrawl`].  The variant holds the name of the placeholder.
+    #[display("Unknown placeholder: {_0}")]
+    Placeholder(#[error(not(source))] String),
+    /// The `Unbound` variant indicates an act in `Tardy.toml` has an empty binding.  The variant
+    /// holds the name of the act.
+    #[display("No key bound to {_0}")]
+    Unbound(#[error(not(source))] String),
+    /// The `Unclosed` variant indicates a template opens a placeholder with `{` and never closes
+    /// it.  The variant holds the offending template.
+    #[display("Unclosed placeholder in template: {_0}")]
+    Unclosed(#[error(not(source))] String),
+    /// The `UnknownMacro` variant indicates a binding refers to a macro missing from the
+    /// `[macros]` table.  The variant holds the name of the macro.
+    #[display("Unknown macro: {_0}")]
+    UnknownMacro(#[error(not(source))] String),
+    /// The `UnknownMode` variant indicates a switch to a mode with no keymap.  The variant holds
+    /// the name of the mode.
+    #[display("Unknown mode: {_0}")]
+    UnknownMode(#[error(not(source))] String),
+    /// The `UnknownPreset` variant indicates the `keymap` in `Tardy.toml` does not name a
+    /// [`crate::Preset`].  The variant holds the name.
+    #[display("Unknown keymap preset: {_0}")]
+    UnknownPreset(#[error(not(source))] String),
+    /// The `UnknownAct` variant indicates a key in `Tardy.toml` does not name an [`crate::Act`].
+    /// The variant holds the key.
+    #[display("Unknown act: {_0}")]
+    UnknownAct(#[error(not(source))] String),
 }
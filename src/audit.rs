@@ -0,0 +1,268 @@
+use crate::{Arrive, Quote, Quotes, Row, Tome};
+use std::collections::HashMap;
+use std::{fs, path};
+
+/// The `audit` module holds the [`Audit`] struct, a validation report on a file of quotes.
+///
+/// # Auditing the Quotes
+///
+/// [`Quotes::from_path`] is forgiving.  It warns about rows that fail to read and carries on,
+/// without saying which row, or how many.  It also cheerfully accepts empty quotes, the same
+/// quote twice, stray whitespace, and the curly quotation marks that word processors love to
+/// sneak into text.  None of this stops the imps from doing their job, but a careful curator of
+/// quotes would like to know.
+///
+/// An `Audit` reads a file of quotes row by row using [`Tome::rows`], and records a [`Finding`]
+/// for each problem, along with the row it came from.  Along the way, it cleans up what it can
+/// using [`Quote::tidy`], and holds on to the tidy, deduplicated quotes in the `quotes` field.
+///
+/// The same report is available from the command line with `tardy quotes check`.
+///
+/// * The `total` field holds the number of rows read.
+/// * The `findings` field holds the problems found, in row order.
+/// * The `quotes` field holds the quotes that survived, tidied up and without duplicates.
+#[derive(Debug, Default, Clone, PartialEq, Eq, derive_getters::Getters)]
+pub struct Audit {
+    total: usize,
+    findings: Vec<Finding>,
+    quotes: Quotes,
+}
+
+/// The `Finding` struct records a single problem found by an [`Audit`].
+///
+/// * The `row` field holds the row number, see [`Tome::rows`].
+/// * The `flaw` field describes the problem.
+/// * The `text` field holds the offending text, or the error message for a row that failed to
+///   read.
+#[derive(
+    Debug, Clone, PartialEq, Eq, derive_new::new, derive_getters::Getters, derive_more::Display,
+)]
+#[display("row {row}: {flaw}: {text}")]
+pub struct Finding {
+    row: usize,
+    flaw: Flaw,
+    text: String,
+}
+
+/// The `Flaw` enum describes the kinds of problems an [`Audit`] looks for.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, derive_more::Display)]
+pub enum Flaw {
+    /// The row could not be read as a [`Quote`].  The row is dropped.
+    #[display("malformed")]
+    Malformed,
+    /// The quote is empty, or nothing but whitespace.  The row is dropped.
+    #[display("empty quote")]
+    Empty,
+    /// The quote or author has leading, trailing or repeated whitespace.  The row is kept, tidied.
+    #[display("stray whitespace")]
+    Whitespace,
+    /// The quote or author contains curly quotation marks.  The row is kept, tidied.
+    #[display("typographic quotes")]
+    Typographic,
+    /// The quote and author exactly match an earlier row, after tidying.  The row is dropped.
+    #[display("duplicate of row {_0}")]
+    Duplicate(usize),
+    /// The quote matches an earlier row when ignoring case, punctuation and author, but is not an
+    /// exact duplicate.  The row is kept, since only a human can decide which one is right.
+    #[display("near duplicate of row {_0}")]
+    NearDuplicate(usize),
+}
+
+impl Flaw {
+    /// The `is_fatal` method returns `true` for flaws that cause the [`Audit`] to drop the row.
+    pub fn is_fatal(&self) -> bool {
+        matches!(self, Self::Malformed | Self::Empty | Self::Duplicate(_))
+    }
+}
+
+impl Audit {
+    /// The `from_path` method audits the quotes in the file at `path`, reading the format using
+    /// [`Tome::from_path`].  Will [`crate::Blame::Io`] if the file cannot be read, and will return
+    /// a parsing error if a JSON, TOML or YAML document fails to parse as a whole.
+    pub fn from_path(path: &path::Path) -> Arrive<Self> {
+        let contents = fs::read_to_string(path)?;
+        let tome = Tome::from_path(path, &contents);
+        Self::from_str(&contents, tome)
+    }
+
+    /// The `from_str` method audits the quotes in `contents`, read as the format `tome`.
+    pub fn from_str(contents: &str, tome: Tome) -> Arrive<Self> {
+        Ok(Self::from_rows(tome.rows(contents)?))
+    }
+
+    /// The `from_rows` method audits the quotes in `rows`.  This is where the real work happens.
+    ///
+    /// For each row, we check that the quote read successfully and is not empty.  We tidy the
+    /// quote using [`Quote::tidy`], noting whether tidying changed the whitespace or the
+    /// quotation marks.  Then we look for duplicates, keeping two lookup tables: one keyed by the
+    /// tidy quote and author for exact duplicates, and one keyed by [`fold`] of the quote text
+    /// for near duplicates.
+    pub fn from_rows(rows: Vec<Row>) -> Self {
+        let mut audit = Self {
+            total: rows.len(),
+            ..Default::default()
+        };
+        let mut exact = HashMap::new();
+        let mut near = HashMap::new();
+        for row in rows {
+            let (row, entry) = (*row.row(), row.entry().clone());
+            let quote = match entry {
+                Ok(quote) => quote,
+                Err(e) => {
+                    audit.flag(row, Flaw::Malformed, e);
+                    continue;
+                }
+            };
+            if quote.quote().trim().is_empty() {
+                audit.flag(row, Flaw::Empty, quote.graffiti());
+                continue;
+            }
+            let tidy = quote.tidy();
+            if Self::spacing(&quote) != Self::spacing(&tidy) {
+                audit.flag(row, Flaw::Whitespace, quote.graffiti());
+            }
+            if Self::has_curls(&quote) {
+                audit.flag(row, Flaw::Typographic, quote.graffiti());
+            }
+            let key = (tidy.author().clone(), tidy.quote().clone());
+            if let Some(first) = exact.get(&key) {
+                audit.flag(row, Flaw::Duplicate(*first), tidy.graffiti());
+                continue;
+            }
+            exact.insert(key, row);
+            let folded = fold(tidy.quote());
+            if let Some(first) = near.get(&folded) {
+                audit.flag(row, Flaw::NearDuplicate(*first), tidy.graffiti());
+            } else {
+                near.insert(folded, row);
+            }
+            audit.quotes.push(tidy);
+        }
+        audit
+    }
+
+    /// The `flag` method records a [`Finding`].
+    fn flag(&mut self, row: usize, flaw: Flaw, text: String) {
+        self.findings.push(Finding::new(row, flaw, text));
+    }
+
+    /// The `spacing` method strips everything but the whitespace layout from a quote, so we can
+    /// tell whether [`Quote::tidy`] changed the whitespace, as opposed to the quotation marks.
+    fn spacing(quote: &Quote) -> (Option<String>, String) {
+        let plain = |s: &str| s.replace(['“', '”', '„'], "\"").replace(['‘', '’'], "'");
+        (quote.author().as_deref().map(plain), plain(quote.quote()))
+    }
+
+    /// The `has_curls` method returns `true` if the quote or author contains any of the curly
+    /// quotation marks replaced by [`Quote::tidy`].
+    fn has_curls(quote: &Quote) -> bool {
+        let curly = |s: &str| s.contains(['“', '”', '„', '‘', '’']);
+        curly(quote.quote()) || quote.author().as_deref().is_some_and(curly)
+    }
+
+    /// The `is_clean` method returns `true` if the audit found nothing to complain about.
+    pub fn is_clean(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    /// The `dropped` method returns the number of rows that did not make it into `quotes`.
+    pub fn dropped(&self) -> usize {
+        self.total - self.quotes.len()
+    }
+
+    /// The `count` method returns the number of findings for which `predicate` returns `true`.
+    pub fn count<F: Fn(&Flaw) -> bool>(&self, predicate: F) -> usize {
+        self.findings.iter().filter(|f| predicate(f.flaw())).count()
+    }
+
+    /// The `report` method formats the audit for a human, with one line per finding followed by
+    /// a summary of the totals.
+    pub fn report(&self) -> String {
+        let mut report = self
+            .findings
+            .iter()
+            .map(|finding| format!("{finding}\n"))
+            .collect::<String>();
+        report.push_str(&format!(
+            "{} rows, {} quotes kept, {} dropped, {} findings ({} malformed, {} empty, {} duplicate, {} near duplicate, {} whitespace, {} typographic)\n",
+            self.total,
+            self.quotes.len(),
+            self.dropped(),
+            self.findings.len(),
+            self.count(|f| matches!(f, Flaw::Malformed)),
+            self.count(|f| matches!(f, Flaw::Empty)),
+            self.count(|f| matches!(f, Flaw::Duplicate(_))),
+            self.count(|f| matches!(f, Flaw::NearDuplicate(_))),
+            self.count(|f| matches!(f, Flaw::Whitespace)),
+            self.count(|f| matches!(f, Flaw::Typographic)),
+        ));
+        report
+    }
+}
+
+/// The `fold` function reduces the text of a quote to lower case words, discarding punctuation,
+/// so that quotes differing only in case or punctuation fold to the same key.
+pub fn fold(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flaws(audit: &Audit) -> Vec<(usize, Flaw)> {
+        audit
+            .findings()
+            .iter()
+            .map(|finding| (*finding.row(), finding.flaw().clone()))
+            .collect()
+    }
+
+    #[test]
+    fn clean_file() {
+        let audit = Audit::from_str("Author,Quote\nAda,Hello.\nBob,Goodbye.", Tome::Csv).unwrap();
+        assert!(audit.is_clean());
+        assert_eq!(*audit.total(), 2);
+        assert_eq!(audit.quotes().len(), 2);
+    }
+
+    #[test]
+    fn flaws_by_row() {
+        let contents = [
+            r#"{"author": "Ada", "quote": "Hello."}"#,
+            r#"{"author": "Ada""#,
+            r#"{"author": "Bob", "quote": "  "}"#,
+            r#"{"author": "Ada", "quote": "Hello."}"#,
+            r#"{"author": "Cy", "quote": "hello"}"#,
+            r#"{"author": "Di", "quote": "Two  spaces."}"#,
+            r#"{"author": "Ed", "quote": "“Curly.”"}"#,
+        ]
+        .join("\n");
+        let audit = Audit::from_str(&contents, Tome::JsonLines).unwrap();
+        assert_eq!(
+            flaws(&audit),
+            vec![
+                (2, Flaw::Malformed),
+                (3, Flaw::Empty),
+                (4, Flaw::Duplicate(1)),
+                (5, Flaw::NearDuplicate(1)),
+                (6, Flaw::Whitespace),
+                (7, Flaw::Typographic),
+            ]
+        );
+        assert_eq!(*audit.total(), 7);
+        assert_eq!(audit.dropped(), 3);
+        assert_eq!(audit.quotes()[2].quote(), "Two spaces.");
+        assert_eq!(audit.quotes()[3].quote(), "\"Curly.\"");
+    }
+
+    #[test]
+    fn fold_ignores_case_and_punctuation() {
+        assert_eq!(fold("Hello, World!"), "hello world");
+        assert_eq!(fold("hello world"), fold("HELLO... world?"));
+    }
+}
@@ -0,0 +1,239 @@
+use crate::{Act, Arrive, Chord, Sequence};
+use std::{fmt, fs, path};
+use strum::IntoEnumIterator;
+
+/// The `capture` module holds the [`Capture`] struct, which walks the user through rebinding a
+/// key while the program runs.
+///
+/// # Rebinding Keys on the Fly
+///
+/// Changing a binding used to mean editing `Tardy.toml` by hand and restarting the program.  Now
+/// the user can press the key bound to [`Act::Rebind`] to start a `Capture`, which goes in two
+/// steps:
+///
+/// 1. Pick the act to rebind.  The window title shows the act on offer, like
+///    `Tardy [rebind: new_window]`.  `Tab` or the arrow keys move through the acts, `Enter`
+///    picks one.
+/// 2. Press the new chord.  The title changes to `Tardy [press key: new_window]`, and the next
+///    chord pressed becomes the binding for the act, replacing any old bindings in the global
+///    keymap, see [`crate::Cmd::rebind`].
+///
+/// `Escape` cancels the capture at either step, so `Escape` itself cannot be bound this way.  If
+/// the chord already belongs to another act, the [`crate::App`] reports the conflict and waits
+/// for a different chord.  Once the keymap takes the new binding, [`Capture::save`] writes it
+/// back to `Tardy.toml`, leaving the comments and other settings in the file as they were.
+///
+/// Modes and their `switch_mode` tables are out of reach of a capture, since there is no good
+/// way to pick a mode by pressing keys.  Those still call for a text editor.
+#[derive(Debug, Clone, PartialEq, Eq, derive_getters::Getters)]
+pub struct Capture {
+    acts: Vec<Act>,
+    index: usize,
+    pressing: bool,
+}
+
+/// The `Step` enum describes the outcome of feeding a [`Chord`] to a [`Capture`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    /// The `Wait` variant indicates the capture needs more keys.
+    Wait,
+    /// The `Cancel` variant indicates the user gave up on the capture.
+    Cancel,
+    /// The `Bind` variant holds the act picked by the user and the keys to bind to it.
+    Bind(Act, Sequence),
+}
+
+/// The `CONFIG_PATH` constant holds the location of the configuration file that
+/// [`Capture::save`] writes new bindings to.
+pub const CONFIG_PATH: &str = "Tardy.toml";
+
+impl Default for Capture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Capture {
+    /// The `new` method starts a capture at the first step, offering each [`Act`] that a single
+    /// key can stand for.  [`Act::SwitchMode`] needs the name of a mode, [`Act::ResizeWindow`]
+    /// needs a size, [`Act::Macro`] and [`Act::Custom`] need a name, and [`Act::Be`] is not worth
+    /// a key, so none of them are on offer.
+    pub fn new() -> Self {
+        let acts = Act::iter()
+            .filter(|act| {
+                !matches!(
+                    act,
+                    Act::SwitchMode { .. }
+                        | Act::ResizeWindow { .. }
+                        | Act::Macro { .. }
+                        | Act::Custom { .. }
+                        | Act::Be
+                )
+            })
+            .collect();
+        Self {
+            acts,
+            index: 0,
+            pressing: false,
+        }
+    }
+
+    /// The `act` method returns the act currently on offer.
+    pub fn act(&self) -> &Act {
+        &self.acts[self.index]
+    }
+
+    /// The `feed` method moves the capture along with the `chord` pressed by the user.
+    pub fn feed(&mut self, chord: &Chord) -> Step {
+        if chord.key() == "Escape" {
+            tracing::info!("Rebinding cancelled.");
+            return Step::Cancel;
+        }
+        if self.pressing {
+            return Step::Bind(self.act().clone(), Sequence::new(vec![chord.clone()]));
+        }
+        let back = chord.mods().shift_key();
+        match chord.key().as_str() {
+            "Tab" if back => self.turn(false),
+            "Tab" | "ArrowRight" | "ArrowDown" => self.turn(true),
+            "ArrowLeft" | "ArrowUp" => self.turn(false),
+            "Enter" => {
+                self.pressing = true;
+                tracing::info!("Press the new key for {}.", self.act().snake());
+            }
+            _ => {}
+        }
+        Step::Wait
+    }
+
+    /// The `turn` method offers the next act, or the previous one if `forward` is `false`,
+    /// wrapping around at either end.
+    fn turn(&mut self, forward: bool) {
+        let count = self.acts.len();
+        self.index = if forward {
+            (self.index + 1) % count
+        } else {
+            (self.index + count - 1) % count
+        };
+    }
+
+    /// The `save` method writes `keys` as the binding for `act` to the top level of the
+    /// configuration file at `path`, creating the file if needed.  We edit the file with
+    /// [`toml_edit`] instead of serializing the [`config::Config`], so the comments and
+    /// formatting of the file survive, along with any comment trailing the old binding.
+    ///
+    /// Will [`crate::Blame::Io`] if the file cannot be read or written, and
+    /// [`crate::Blame::TomlEdit`] if the file is not valid TOML.
+    pub fn save(path: &path::Path, act: &Act, keys: &[Sequence]) -> Arrive<()> {
+        let contents = if path.exists() {
+            fs::read_to_string(path)?
+        } else {
+            String::new()
+        };
+        let mut doc = contents.parse::<toml_edit::DocumentMut>()?;
+        let mut value = match keys {
+            [keys] => toml_edit::Value::from(keys.to_string()),
+            _ => toml_edit::Value::Array(keys.iter().map(|keys| keys.to_string()).collect()),
+        };
+        let name = act.snake();
+        if let Some(old) = doc.get(&name).and_then(toml_edit::Item::as_value) {
+            *value.decor_mut() = old.decor().clone();
+        }
+        doc[&name] = toml_edit::Item::Value(value);
+        fs::write(path, doc.to_string())?;
+        tracing::info!("Saved the binding for {name} to {}", path.display());
+        Ok(())
+    }
+}
+
+impl fmt::Display for Capture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.pressing {
+            write!(f, "press key: {}", self.act().snake())
+        } else {
+            write!(f, "rebind: {}", self.act().snake())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(capture: &mut Capture, text: &str) -> Step {
+        capture.feed(&text.parse().unwrap())
+    }
+
+    fn scratch(name: &str) -> path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("tardy-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn feed_picks_act_then_keys() {
+        let mut capture = Capture::new();
+        let first = capture.act().clone();
+        assert_eq!(feed(&mut capture, "Tab"), Step::Wait);
+        let second = capture.act().clone();
+        assert_ne!(first, second);
+        assert_eq!(feed(&mut capture, "Shift+Tab"), Step::Wait);
+        assert_eq!(capture.act(), &first);
+        assert_eq!(feed(&mut capture, "ArrowUp"), Step::Wait);
+        assert_eq!(capture.act(), capture.acts().last().unwrap());
+        assert_eq!(feed(&mut capture, "ArrowDown"), Step::Wait);
+        assert_eq!(feed(&mut capture, "q"), Step::Wait);
+        assert_eq!(capture.to_string(), format!("rebind: {}", first.snake()));
+        assert_eq!(feed(&mut capture, "Enter"), Step::Wait);
+        assert_eq!(capture.to_string(), format!("press key: {}", first.snake()));
+        assert_eq!(
+            feed(&mut capture, "Ctrl+q"),
+            Step::Bind(first, "Ctrl+q".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn escape_cancels_either_step() {
+        let mut capture = Capture::new();
+        assert_eq!(feed(&mut capture, "Escape"), Step::Cancel);
+        let mut capture = Capture::new();
+        feed(&mut capture, "Enter");
+        assert_eq!(feed(&mut capture, "Escape"), Step::Cancel);
+    }
+
+    #[test]
+    fn save_changes_only_the_binding() {
+        let path = scratch("capture-save").join("Tardy.toml");
+        let before = "# Keys first.\n\
+                      keymap = \"default\"\n\
+                      exit = \"Escape\" # Bail out.\n\
+                      new_window = \"n\"\n\
+                      \n\
+                      # Quotes next.\n\
+                      [quotes]\n\
+                      watch = false # No polling.\n";
+        fs::write(&path, before).unwrap();
+        Capture::save(&path, &Act::Exit, &["Ctrl+q".parse().unwrap()]).unwrap();
+        let after = fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            after,
+            before.replace("exit = \"Escape\"", "exit = \"Ctrl+q\"")
+        );
+    }
+
+    #[test]
+    fn save_creates_the_file() {
+        let path = scratch("capture-create").join("Tardy.toml");
+        let keys = ["n".parse().unwrap(), "Ctrl+n".parse().unwrap()];
+        Capture::save(&path, &Act::NewWindow { count: None }, &keys).unwrap();
+        let config = config::Config::builder()
+            .add_source(config::File::from(path.as_path()))
+            .build()
+            .unwrap();
+        assert_eq!(
+            config.get::<Vec<String>>("new_window").unwrap(),
+            vec!["n", "Ctrl+n"]
+        );
+    }
+}
@@ -0,0 +1,462 @@
+use crate::{Arrive, Blame, Excuse};
+use serde::de::IntoDeserializer;
+use serde::Deserialize;
+use std::str::FromStr;
+use winit::{event, keyboard};
+
+/// The `chord` module holds the [`Chord`] struct, a key pressed together with zero or more
+/// modifier keys.
+///
+/// # Playing Chords
+///
+/// The first version of [`crate::Cmd`] matched on the bare key, so `n` opened a window whether or
+/// not the user was holding down `Ctrl`, and there was no way to bind `Ctrl+n` to something else.
+/// Now the values in `Tardy.toml` are chords, written as modifier names and a key joined by `+`:
+///
+/// ```toml
+/// new_window = "Ctrl+n"
+/// exit = "Shift+Escape"
+/// close_window = "Alt+Super+x"
+/// ```
+///
+/// The modifiers are `Ctrl`, `Alt`, `Shift` and `Super`, matched without regard to case, and
+/// `Control`, `Option`, `Cmd`, `Meta`, `Win` and `Logo` work as aliases.  The key is either a
+/// single character, or the name of a [`keyboard::NamedKey`] such as `Escape`, `Enter` or
+/// `ArrowUp`.  A chord may end in `+` to bind the plus key itself, as in `Ctrl++`.
+///
+/// Every chord has a canonical spelling, produced by the [`std::fmt::Display`] implementation:
+/// modifiers in the order `Ctrl`, `Alt`, `Shift`, `Super`, then the key, with character keys in
+/// lower case.  Parsing the canonical spelling gives back the same chord, so we can write chords
+/// back out to the user in messages and files.
+///
+/// A [`Sequence`] strings several chords together, separated by spaces, like `"g g"` or
+/// `"Ctrl+x Ctrl+c"`.
+///
+/// By default a chord names a logical key, the character the keyboard layout produces, so `n`
+/// means whatever key types an `n`.  On a layout other than QWERTY, that key may sit somewhere
+/// else entirely.  To bind the key by position instead, prefix the key with `physical:` and use
+/// the name of the [`keyboard::KeyCode`], like `"physical:KeyN"` or `"Ctrl+physical:Digit1"`.
+/// Physical names are case sensitive.  Both forms can live in the same keymap.
+///
+/// The mouse plays chords too.  Mouse buttons are named `MouseLeft`, `MouseRight`, `MouseMiddle`,
+/// `MouseBack` and `MouseForward`, with any other buttons numbered like `Mouse8`.  Put `Double`
+/// in front of a button to bind a double click, like `DoubleMouseLeft`.  Scrolling the wheel plays
+/// `WheelUp`, `WheelDown`, `WheelLeft` or `WheelRight`.  Modifiers work the same as they do for
+/// keys, so `"Ctrl+WheelUp"` is fair game.
+///
+/// The [`winit`] event loop reports the state of the modifier keys in a separate
+/// [`event::WindowEvent::ModifiersChanged`] event, so [`crate::App`] keeps track of the latest
+/// state and hands it to [`Chord::from_event`] along with each key press.
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, derive_getters::Getters)]
+pub struct Chord {
+    mods: keyboard::ModifiersState,
+    key: String,
+    physical: bool,
+}
+
+/// The `ANY_KEY` constant holds the name of a key that matches any key press, mouse click or turn
+/// of the wheel that no other binding claims, see [`Chord::any`].
+pub const ANY_KEY: &str = "Any";
+
+/// The `PHYSICAL` constant holds the prefix marking a [`Chord`] that names a physical key.
+pub const PHYSICAL: &str = "physical:";
+
+impl Chord {
+    /// The `new` method creates a `Chord` from the modifier state in `mods` and the name of the
+    /// `key`.  Character keys are lowered to canonical case.
+    pub fn new(mods: keyboard::ModifiersState, key: &str) -> Self {
+        Self {
+            mods,
+            key: Self::canon(key),
+            physical: false,
+        }
+    }
+
+    /// The `scancode` method creates a `Chord` from the modifier state in `mods` and the name of
+    /// the physical `key`, a variant of [`keyboard::KeyCode`].
+    pub fn scancode(mods: keyboard::ModifiersState, key: &str) -> Self {
+        Self {
+            mods,
+            key: key.to_string(),
+            physical: true,
+        }
+    }
+
+    /// The `from_physical` method creates a `Chord` from the physical key of `event` and the
+    /// modifier state in `mods`.  Returns [`None`] if the platform could not identify the key.
+    pub fn from_physical(event: &event::KeyEvent, mods: keyboard::ModifiersState) -> Option<Self> {
+        Self::from_code(&event.physical_key, mods)
+    }
+
+    /// The `from_code` method does the work of [`Chord::from_physical`] for the physical key
+    /// `code`, apart from the rest of the event.
+    pub fn from_code(code: &keyboard::PhysicalKey, mods: keyboard::ModifiersState) -> Option<Self> {
+        match code {
+            keyboard::PhysicalKey::Code(code) => Some(Self::scancode(mods, &format!("{code:?}"))),
+            keyboard::PhysicalKey::Unidentified(_) => None,
+        }
+    }
+
+    /// The `from_event` method creates a `Chord` from the logical key of `event` and the modifier
+    /// state in `mods`.  Returns [`None`] for keys that have no name we can bind, like dead keys.
+    pub fn from_event(event: &event::KeyEvent, mods: keyboard::ModifiersState) -> Option<Self> {
+        Self::from_key(&event.logical_key, mods)
+    }
+
+    /// The `from_key` method does the work of [`Chord::from_event`] for the logical `key`, apart
+    /// from the rest of the event.
+    pub fn from_key(key: &keyboard::Key, mods: keyboard::ModifiersState) -> Option<Self> {
+        match key.as_ref() {
+            keyboard::Key::Named(k) => Some(Self::new(mods, &format!("{k:?}"))),
+            keyboard::Key::Character(k) => Some(Self::new(mods, k)),
+            other => {
+                tracing::trace!("Unrecognized key event: {other:?}");
+                None
+            }
+        }
+    }
+
+    /// The `from_mouse` method creates a `Chord` for a click of the mouse `button`, or a double
+    /// click when `double` is `true`, with the modifier state in `mods`.
+    pub fn from_mouse(
+        button: event::MouseButton,
+        double: bool,
+        mods: keyboard::ModifiersState,
+    ) -> Self {
+        let name = match button {
+            event::MouseButton::Left => "MouseLeft".to_string(),
+            event::MouseButton::Right => "MouseRight".to_string(),
+            event::MouseButton::Middle => "MouseMiddle".to_string(),
+            event::MouseButton::Back => "MouseBack".to_string(),
+            event::MouseButton::Forward => "MouseForward".to_string(),
+            event::MouseButton::Other(n) => format!("Mouse{n}"),
+        };
+        if double {
+            Self::new(mods, &format!("Double{name}"))
+        } else {
+            Self::new(mods, &name)
+        }
+    }
+
+    /// The `from_wheel` method creates a `Chord` for a turn of the mouse wheel, with the modifier
+    /// state in `mods`.  The `x` and `y` arguments follow the sign convention of
+    /// [`event::MouseScrollDelta`], where positive values scroll up and to the left, and we name
+    /// the chord after whichever direction moved the most.  Returns [`None`] if neither did.
+    pub fn from_wheel(x: f64, y: f64, mods: keyboard::ModifiersState) -> Option<Self> {
+        let name = if x == 0.0 && y == 0.0 {
+            return None;
+        } else if y.abs() >= x.abs() {
+            if y > 0.0 {
+                "WheelUp"
+            } else {
+                "WheelDown"
+            }
+        } else if x > 0.0 {
+            "WheelLeft"
+        } else {
+            "WheelRight"
+        };
+        Some(Self::new(mods, name))
+    }
+
+    /// The `any` method returns the chord named by [`ANY_KEY`], with no modifiers.  Bind it to
+    /// catch every input that has no binding of its own, like `close_window = "Any"`.
+    pub fn any() -> Self {
+        Self::new(keyboard::ModifiersState::empty(), ANY_KEY)
+    }
+
+    /// The `is_modifier` method returns `true` if `event` is a press of a modifier key like
+    /// `Shift` or `Ctrl`.  On its own, a modifier is not much of a key press, so it does not count
+    /// as [`ANY_KEY`].
+    pub fn is_modifier(event: &event::KeyEvent) -> bool {
+        Self::is_modifier_key(&event.logical_key)
+    }
+
+    /// The `is_modifier_key` method does the work of [`Chord::is_modifier`] for the logical `key`.
+    pub fn is_modifier_key(key: &keyboard::Key) -> bool {
+        use keyboard::NamedKey;
+        matches!(
+            key,
+            keyboard::Key::Named(
+                NamedKey::Shift
+                    | NamedKey::Control
+                    | NamedKey::Alt
+                    | NamedKey::AltGraph
+                    | NamedKey::Super
+                    | NamedKey::Meta
+                    | NamedKey::Hyper
+                    | NamedKey::CapsLock
+                    | NamedKey::NumLock
+                    | NamedKey::Fn
+            )
+        )
+    }
+
+    /// The `unshifted` method returns the chord without the `Shift` modifier, if the key is a
+    /// logical character other than a letter.  Shift usually changes the character itself, so
+    /// that `Shift+=` arrives as `+` on many keyboards, and a binding for `+` should still match.
+    /// Letters are left alone, since `Shift+n` is a chord of its own that the user may well want
+    /// to leave unbound.  Returns [`None`] when the chord has no shift to remove, or the key is a
+    /// letter, a named key or a physical key.
+    pub fn unshifted(&self) -> Option<Self> {
+        let mut chars = self.key.chars();
+        let symbol = match (chars.next(), chars.next()) {
+            (Some(c), None) => !c.is_alphabetic(),
+            _ => false,
+        };
+        if self.mods.shift_key() && !self.physical && symbol {
+            Some(Self {
+                mods: self.mods - keyboard::ModifiersState::SHIFT,
+                key: self.key.clone(),
+                physical: false,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// The `canon` method lowers single characters to canonical case, and leaves named keys
+    /// alone.
+    fn canon(key: &str) -> String {
+        if key.chars().count() == 1 {
+            key.to_lowercase()
+        } else {
+            key.to_string()
+        }
+    }
+
+    /// The `modifier` method maps the name of a modifier key to the corresponding flag.
+    fn modifier(name: &str) -> Option<keyboard::ModifiersState> {
+        match name.to_lowercase().as_str() {
+            "ctrl" | "control" => Some(keyboard::ModifiersState::CONTROL),
+            "alt" | "option" => Some(keyboard::ModifiersState::ALT),
+            "shift" => Some(keyboard::ModifiersState::SHIFT),
+            "super" | "cmd" | "meta" | "win" | "logo" => Some(keyboard::ModifiersState::SUPER),
+            _ => None,
+        }
+    }
+
+    /// The `is_code` method returns `true` if `key` names a variant of [`keyboard::KeyCode`], the
+    /// only names a physical key can have.  We lean on the [`serde`] support in [`winit`] to
+    /// read the name, rather than keep a list of our own.
+    pub fn is_code(key: &str) -> bool {
+        let name: serde::de::value::StrDeserializer<serde::de::value::Error> =
+            key.into_deserializer();
+        keyboard::KeyCode::deserialize(name).is_ok()
+    }
+}
+
+/// Parsing a chord splits the text on `+`, treating the last piece as the key and the rest as
+/// modifiers.  A trailing `++`, or a lone `+`, binds the plus key.  A key starting with
+/// [`PHYSICAL`] names a physical key.  We also accept the prefix at the very front, as in
+/// `physical:Ctrl+KeyN`, because that is where people tend to put it.
+///
+/// Will [`Excuse::Chord`] if a modifier has an unknown name, the key is missing, or a physical
+/// key is not a [`keyboard::KeyCode`], like `physical:Keyn`, which would never fire.
+impl FromStr for Chord {
+    type Err = Blame;
+
+    fn from_str(text: &str) -> Arrive<Self> {
+        let bad = || Blame::Excuse(Excuse::Chord(text.to_string()));
+        let mut text = text.trim();
+        let mut physical = false;
+        if let Some(rest) = text.strip_prefix(PHYSICAL) {
+            physical = true;
+            text = rest;
+        }
+        let (prefix, key) = if text == "+" {
+            ("", "+")
+        } else if let Some(prefix) = text.strip_suffix("++") {
+            (prefix, "+")
+        } else {
+            text.rsplit_once('+').unwrap_or(("", text))
+        };
+        let mut key = key.trim();
+        if let Some(rest) = key.strip_prefix(PHYSICAL) {
+            physical = true;
+            key = rest;
+        }
+        if key.is_empty() || (physical && !Self::is_code(key)) {
+            return Err(bad());
+        }
+        let mut mods = keyboard::ModifiersState::empty();
+        for name in prefix.split('+').map(str::trim).filter(|n| !n.is_empty()) {
+            mods |= Self::modifier(name).ok_or_else(bad)?;
+        }
+        if physical {
+            Ok(Self::scancode(mods, key))
+        } else {
+            Ok(Self::new(mods, key))
+        }
+    }
+}
+
+impl std::fmt::Display for Chord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.mods.control_key() {
+            write!(f, "Ctrl+")?;
+        }
+        if self.mods.alt_key() {
+            write!(f, "Alt+")?;
+        }
+        if self.mods.shift_key() {
+            write!(f, "Shift+")?;
+        }
+        if self.mods.super_key() {
+            write!(f, "Super+")?;
+        }
+        if self.physical {
+            write!(f, "{PHYSICAL}")?;
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+/// The `Sequence` struct is a wrapper around a vector of [`Chord`], the keys pressed one after
+/// another to trigger a binding in [`crate::Cmd`].  Most sequences hold a single chord.
+///
+/// A sequence is written as chords separated by whitespace, which means a chord itself may not
+/// contain spaces.  The canonical spelling joins the canonical chords with a single space.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    derive_new::new,
+    derive_more::Deref,
+    derive_more::DerefMut,
+)]
+pub struct Sequence(Vec<Chord>);
+
+/// Parsing a sequence parses each whitespace-separated piece as a [`Chord`].
+///
+/// Will [`Excuse::Chord`] if any chord fails to parse, or if there are no chords at all.
+impl FromStr for Sequence {
+    type Err = Blame;
+
+    fn from_str(text: &str) -> Arrive<Self> {
+        let chords = text
+            .split_whitespace()
+            .map(Chord::from_str)
+            .collect::<Arrive<Vec<Chord>>>()?;
+        if chords.is_empty() {
+            return Err(Blame::Excuse(Excuse::Chord(text.to_string())));
+        }
+        Ok(Self(chords))
+    }
+}
+
+impl std::fmt::Display for Sequence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let chords = self.iter().map(Chord::to_string).collect::<Vec<String>>();
+        write!(f, "{}", chords.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chord(text: &str) -> Chord {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn unshifted_symbols_only() {
+        assert_eq!(chord("Shift++").unshifted(), Some(chord("+")));
+        assert_eq!(chord("Ctrl+Shift+:").unshifted(), Some(chord("Ctrl+:")));
+        assert_eq!(chord("Shift+n").unshifted(), None);
+        assert_eq!(chord("Shift+Escape").unshifted(), None);
+        assert_eq!(chord("Shift+physical:Equal").unshifted(), None);
+        assert_eq!(chord("+").unshifted(), None);
+    }
+
+    #[test]
+    fn wheel_follows_the_larger_turn() {
+        let none = keyboard::ModifiersState::empty();
+        let wheel = |x, y| Chord::from_wheel(x, y, none).map(|chord| chord.to_string());
+        assert_eq!(wheel(0.0, 0.0), None);
+        assert_eq!(wheel(0.0, 1.0).as_deref(), Some("WheelUp"));
+        assert_eq!(wheel(0.5, -1.0).as_deref(), Some("WheelDown"));
+        assert_eq!(wheel(2.0, 1.0).as_deref(), Some("WheelLeft"));
+        assert_eq!(wheel(-2.0, 1.0).as_deref(), Some("WheelRight"));
+        assert_eq!(wheel(1.0, 1.0).as_deref(), Some("WheelUp"));
+        let ctrl = keyboard::ModifiersState::CONTROL;
+        assert_eq!(
+            Chord::from_wheel(0.0, -3.0, ctrl),
+            Some(chord("Ctrl+WheelDown"))
+        );
+    }
+
+    #[test]
+    fn mouse_chords() {
+        let none = keyboard::ModifiersState::empty();
+        let left = event::MouseButton::Left;
+        assert_eq!(Chord::from_mouse(left, false, none), chord("MouseLeft"));
+        assert_eq!(
+            Chord::from_mouse(left, true, none),
+            chord("DoubleMouseLeft")
+        );
+        let other = Chord::from_mouse(event::MouseButton::Other(8), false, none);
+        assert_eq!(other, chord("Mouse8"));
+    }
+
+    #[test]
+    fn spelling_round_trips() {
+        for text in [
+            "+",
+            "Ctrl++",
+            "Ctrl+Alt+Shift+Super+n",
+            "physical:KeyN",
+            "F2",
+        ] {
+            assert_eq!(chord(text).to_string(), text);
+        }
+        assert_eq!(chord("Shift+Ctrl+n").to_string(), "Ctrl+Shift+n");
+        assert_eq!(
+            chord("physical:Ctrl+KeyN").to_string(),
+            "Ctrl+physical:KeyN"
+        );
+        assert_eq!(chord("physical:Ctrl+KeyN"), chord("Ctrl+physical:KeyN"));
+        assert_ne!(chord("physical:KeyN"), chord("KeyN"));
+    }
+
+    #[test]
+    fn bad_chords_fail() {
+        for text in ["", "Ctrl+", "Hyper+n", "physical:"] {
+            assert!(text.parse::<Chord>().is_err(), "{text:?} parsed");
+        }
+    }
+
+    #[test]
+    fn physical_names_are_key_codes() {
+        for text in [
+            "physical:KeyN",
+            "Ctrl+physical:Digit1",
+            "physical:F2",
+            "physical:Space",
+        ] {
+            assert!(text.parse::<Chord>().is_ok(), "{text:?} failed");
+        }
+        for text in [
+            "physical:Keyn",
+            "physical:keyN",
+            "physical:N",
+            "physical:Bogus",
+        ] {
+            assert!(text.parse::<Chord>().is_err(), "{text:?} parsed");
+        }
+    }
+
+    #[test]
+    fn sequence_spelling() {
+        let seq = "g  Ctrl++\tphysical:KeyN".parse::<Sequence>().unwrap();
+        assert_eq!(seq.len(), 3);
+        assert_eq!(seq.to_string(), "g Ctrl++ physical:KeyN");
+        assert!(" ".parse::<Sequence>().is_err());
+    }
+}
@@ -0,0 +1,171 @@
+use crate::{
+    App, Arrive, Audit, Blame, Excuse, Journal, Lore, Quotes, Sheet, Tome, BUILTIN_QUOTES,
+};
+use std::{fs, io, path};
+
+/// The `cli` module holds the command line interface for `tardy`.
+///
+/// # Tardy on the Command Line
+///
+/// Running `tardy` with no arguments opens a window and summons the imps, same as always.  Some
+/// chores do not need a window, like checking a quote file for problems, so `tardy` also accepts
+/// subcommands.  We parse the arguments using the derive API of the [`clap`] crate, which turns
+/// the doc comments on these types into the help text.  Run `tardy --help` for the details.
+#[derive(Debug, clap::Parser)]
+#[command(version, about, long_about = None)]
+pub struct Cli {
+    /// Chore to perform instead of running the application.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// The `Command` enum holds the subcommands of `tardy`.
+#[derive(Debug, clap::Subcommand)]
+pub enum Command {
+    /// Work with quote files.
+    #[command(subcommand)]
+    Quotes(QuotesCommand),
+    /// Look back on the quotes vandalized by the imps.
+    #[command(subcommand)]
+    History(HistoryCommand),
+    /// Print the key bindings configured in Tardy.toml.
+    Keys {
+        /// Format of the output.
+        #[arg(short, long, value_enum, default_value_t = Sheet::Text)]
+        format: Sheet,
+    },
+}
+
+/// The `QuotesCommand` enum holds the subcommands of `tardy quotes`.
+#[derive(Debug, clap::Subcommand)]
+pub enum QuotesCommand {
+    /// Check quote files for malformed rows, empty quotes, duplicates and untidy text.
+    ///
+    /// With no paths, checks the quote files configured in Tardy.toml, or the built-in quotes
+    /// when none are configured.
+    Check {
+        /// Quote files to check, or directories of quote files.
+        paths: Vec<path::PathBuf>,
+    },
+}
+
+/// The `HistoryCommand` enum holds the subcommands of `tardy history`, which read the
+/// [`Journal`] at the path configured in Tardy.toml.
+#[derive(Debug, clap::Subcommand)]
+pub enum HistoryCommand {
+    /// List the most recent quotes, oldest first.
+    List {
+        /// Number of quotes to list.
+        #[arg(short, long, default_value_t = 20)]
+        count: usize,
+    },
+    /// Show the most quoted authors.
+    Authors {
+        /// Number of authors to show.
+        #[arg(short, long, default_value_t = 10)]
+        count: usize,
+    },
+    /// Export the history to CSV.
+    Export {
+        /// File to write, or standard output when absent.
+        path: Option<path::PathBuf>,
+    },
+}
+
+impl Command {
+    /// The `run` method performs the chore described by `self`.
+    pub fn run(&self) -> Arrive<()> {
+        match self {
+            Self::Quotes(cmd) => cmd.run(),
+            Self::History(cmd) => cmd.run(),
+            Self::Keys { format } => {
+                let cmd = App::read_cmd(&App::read_config());
+                print!("{}", format.render(&cmd));
+                Ok(())
+            }
+        }
+    }
+}
+
+impl QuotesCommand {
+    /// The `run` method performs the chore described by `self`.
+    ///
+    /// The `check` subcommand prints an [`Audit::report`] for each file, checking each file
+    /// listed by [`Quotes::files`] when a path is a directory, and will
+    /// [`Excuse::Flawed`] if any audit found problems, so that the process exits with an error
+    /// code suitable for scripts and continuous integration.
+    pub fn run(&self) -> Arrive<()> {
+        match self {
+            Self::Check { paths } => {
+                let paths = if paths.is_empty() {
+                    Lore::from(&App::read_config()).paths().clone()
+                } else {
+                    paths.clone()
+                };
+                let mut audits = Vec::new();
+                if paths.is_empty() {
+                    audits.push((
+                        "built-in quotes".to_string(),
+                        Audit::from_str(BUILTIN_QUOTES, Tome::Csv)?,
+                    ));
+                }
+                for path in paths {
+                    let files = if path.is_dir() {
+                        Quotes::files(&path)?
+                    } else {
+                        vec![path]
+                    };
+                    for file in files {
+                        audits.push((file.display().to_string(), Audit::from_path(&file)?));
+                    }
+                }
+                let mut findings = 0;
+                for (name, audit) in audits {
+                    println!("{name}:");
+                    print!("{}", audit.report());
+                    findings += audit.findings().len();
+                }
+                if findings > 0 {
+                    Err(Blame::Excuse(Excuse::Flawed(findings)))
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
+impl HistoryCommand {
+    /// The `run` method performs the chore described by `self`.
+    ///
+    /// The `list` subcommand prints one line per entry, the `authors` subcommand prints one line
+    /// per author with the number of quotes, and the `export` subcommand writes the whole journal
+    /// using [`Journal::export_csv`].
+    pub fn run(&self) -> Arrive<()> {
+        let lore = Lore::from(&App::read_config());
+        let journal = Journal::load(lore.journal())?;
+        match self {
+            Self::List { count } => {
+                for entry in journal.recent(*count) {
+                    println!(
+                        "{} {}: '{}' - {}",
+                        entry.time().format("%Y-%m-%d %H:%M:%S"),
+                        entry.imp(),
+                        entry.quote(),
+                        entry.author().as_deref().unwrap_or("Unknown")
+                    );
+                }
+            }
+            Self::Authors { count } => {
+                for (author, quotes) in journal.top_authors(*count) {
+                    println!("{quotes:>6}  {author}");
+                }
+            }
+            Self::Export { path } => match path {
+                Some(path) => journal.export_csv(fs::File::create(path)?)?,
+                None => journal.export_csv(io::stdout())?,
+            },
+        }
+        Ok(())
+    }
+}
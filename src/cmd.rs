@@ -1,5 +1,7 @@
-use crate::Act;
-use std::collections::HashMap;
+use crate::{Act, Arrive, Blame, Chord, Excuse, Preset, Routine, Sequence};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 use strum::IntoEnumIterator;
 use winit::{event, keyboard};
 
@@ -8,79 +10,1348 @@ use winit::{event, keyboard};
 ///
 /// # Reading commands from a configuration file with `Cmd`
 ///
-/// The `Cmd` struct is a wrapper around a [`HashMap<String, Act>`], where the [`String`] keys are
-/// the logical key that will serve as the trigger for the [`Act`] contained in the value.  We use
-/// the [`derive_more`] crate to implement [`derive_more::Deref`], and [`derive_more::DerefMut`],
-/// so that we can easily access the methods of the underlying [`HashMap`].
+/// The `Cmd` struct used to be a wrapper around a [`std::collections::HashMap`], where the keys
+/// were the logical keys that triggered each [`Act`].  That works fine for one key at a time, but
+/// a vim user expects `g g` to do something different from `g`, and an emacs user will not rest
+/// until `Ctrl+x Ctrl+c` quits the program.  So now the bindings are a [`Sequence`] of chords,
+/// stored in a prefix tree.  Each node of the tree is reached by pressing the chords along the
+/// path from the root, and holds either an [`Act`], or more nodes to visit.
 ///
-/// We lean on the [`derive_new`] crate for a boilerplate implementation of the [`Cmd::new`]
-/// method.
+/// Matching a key press is a walk down the tree, one chord at a time.  The `pending` field holds
+/// the chords pressed so far.  When the next chord leads to an [`Act`], we return it and go back
+/// to the root.  When it leads to more nodes, we log the partial match, and wait for the next key.
+/// When it leads nowhere, we drop the pending keys and try the chord again from the root, so the
+/// key that broke off the sequence still does its own job.  Pressing `Escape` in
+/// the middle of a sequence cancels it, and so does waiting longer than the `timeout` between
+/// keys, which the user can set in milliseconds in `Tardy.toml`:
+///
+/// ```toml
+/// [sequences]
+/// timeout = 1000
+/// ```
+///
+/// Mouse clicks and turns of the wheel arrive as a [`Chord`] too, see [`Cmd::click`] and
+/// [`Cmd::scroll`], so they can be bound, and even used in sequences, just like keys.
+///
+/// A binding that is the prefix of another binding, like `g` and `g g`, would leave us guessing
+/// whether the user is done typing, so [`Cmd::from_config`] rejects the second one as a conflict.
+///
+/// # Modes
+///
+/// The bindings at the top level of `Tardy.toml` form the global layer.  The user can also define
+/// named modes, each with a keymap of its own, as tables under `[modes]`.  The `switch_mode`
+/// table maps the name of a mode to the keys that switch to it, and can appear at the top level
+/// or inside a mode:
+///
+/// ```toml
+/// [switch_mode]
+/// window = "w"
+///
+/// [modes.window]
+/// new_window = "n"
+/// close_window = "x"
+///
+/// [modes.window.switch_mode]
+/// normal = "Escape"
+/// ```
+///
+/// The program starts out in the [`DEFAULT_MODE`], which needs no table, though it may have one.
+/// In any mode, the keymap of the mode is consulted first, and keys it does not bind fall through
+/// to the global layer.  The current mode shows up in the title of each window, see
+/// [`crate::Lens::show_mode`].
+///
+/// # Macros
+///
+/// The `[macros]` table defines named series of acts, see [`Routine`].  The name of a macro binds
+/// like the name of an act, as in `chaos = "Ctrl+c"`, and the keymap holds the macros in the
+/// `macros` field, so the [`crate::App`] can find the routine to play.
 ///
 /// Maybe this should be named `Command`, but I do not feel like doing the extra typing today.
-#[derive(
-    Debug, Default, Clone, PartialEq, Eq, derive_new::new, derive_more::Deref, derive_more::DerefMut,
-)]
-pub struct Cmd(HashMap<String, Act>);
+#[derive(Debug, Clone, PartialEq, derive_getters::Getters)]
+pub struct Cmd {
+    #[getter(skip)]
+    root: Node,
+    #[getter(skip)]
+    modes: BTreeMap<String, Node>,
+    macros: BTreeMap<String, Routine>,
+    mode: String,
+    timeout: Duration,
+    pending: Vec<Chord>,
+    #[getter(skip)]
+    last: Option<Instant>,
+    /// The last mouse button clicked, and when, for spotting double clicks.
+    #[getter(skip)]
+    clicked: Option<(event::MouseButton, Instant)>,
+    /// Scrolling in pixels not yet large enough to count as a turn of the wheel.
+    #[getter(skip)]
+    scrolled: (f64, f64),
+}
+
+/// The `Node` struct is a node in the prefix tree of a [`Cmd`].  A node with an [`Act`] is a leaf,
+/// since [`Cmd::insert`] refuses to grow a binding out of another binding.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct Node {
+    act: Option<Act>,
+    next: BTreeMap<Chord, Node>,
+}
+
+impl Node {
+    /// The `insert` method binds `keys` to `act` below this node.  If `keys` is already bound, is
+    /// the prefix of another binding, or has a binding as a prefix, we leave the tree alone and
+    /// return the [`Act`] standing in the way.  An empty sequence binds nothing.
+    fn insert(&mut self, keys: &Sequence, act: Act) -> Option<Act> {
+        if keys.is_empty() {
+            return None;
+        }
+        // Look before we leap, so a rejected binding leaves no empty branches behind.
+        let mut node = &*self;
+        for chord in keys.iter() {
+            if let Some(other) = &node.act {
+                return Some(other.clone());
+            }
+            match node.next.get(chord) {
+                Some(next) => node = next,
+                None => break,
+            }
+        }
+        if let Some(other) = self.lookup(keys).and_then(Node::first_act) {
+            return Some(other.clone());
+        }
+        let mut node = self;
+        for chord in keys.iter() {
+            node = node.next.entry(chord.clone()).or_default();
+        }
+        node.act = Some(act);
+        None
+    }
+
+    /// The `lookup` method returns the node reached from this one by pressing `keys`, if any.
+    fn lookup(&self, keys: &[Chord]) -> Option<&Node> {
+        keys.iter()
+            .try_fold(self, |node, chord| node.next.get(chord))
+    }
+
+    /// The `first_act` method returns the first [`Act`] found at or below this node, used to name
+    /// the other party in a conflict.
+    fn first_act(&self) -> Option<&Act> {
+        self.act
+            .as_ref()
+            .or_else(|| self.next.values().find_map(Node::first_act))
+    }
+
+    /// The `remove` method unbinds every sequence bound to `act` at or below this node, pruning
+    /// the branches left empty.
+    fn remove(&mut self, act: &Act) {
+        if self.act.as_ref() == Some(act) {
+            self.act = None;
+        }
+        for node in self.next.values_mut() {
+            node.remove(act);
+        }
+        self.next
+            .retain(|_, node| node.act.is_some() || !node.next.is_empty());
+    }
+
+    /// The `collect` method gathers the bindings at or below this node, with `path` holding the
+    /// chords that lead here.
+    fn collect(&self, path: &mut Vec<Chord>, bindings: &mut Vec<(Sequence, Act)>) {
+        if let Some(act) = &self.act {
+            bindings.push((Sequence::new(path.clone()), act.clone()));
+        }
+        for (chord, node) in &self.next {
+            path.push(chord.clone());
+            node.collect(path, bindings);
+            path.pop();
+        }
+    }
+}
+
+/// The `DEFAULT_KEYMAP` constant pairs the snake case name of each [`Act`] with the keys bound
+/// to it by default, matching the `Tardy.toml` that ships with the program.
+pub const DEFAULT_KEYMAP: [(&str, &str); 7] = [
+    ("exit", "Escape"),
+    ("new_window", "n"),
+    ("close_window", "x"),
+    ("upvote", "+"),
+    ("downvote", "-"),
+    ("rebind", "F2"),
+    ("show_help", "F1"),
+];
+
+/// The `BINDS_KEY` constant holds the name of the list of tables binding keys to acts with
+/// parameters, see [`Act`].
+pub const BINDS_KEY: &str = "binds";
+
+/// The `MACROS_KEY` constant holds the name of the table defining macros, see [`Routine`].
+pub const MACROS_KEY: &str = "macros";
+
+/// The `KEYMAP_KEY` constant holds the key at the top of `Tardy.toml` that picks a [`Preset`].
+pub const KEYMAP_KEY: &str = "keymap";
+
+/// The `DEFAULT_MODE` constant holds the name of the mode the program starts in.
+pub const DEFAULT_MODE: &str = "normal";
+
+/// The `DOUBLE_CLICK` constant holds the longest time between two clicks of the same mouse
+/// button that still counts as a double click, in milliseconds.
+pub const DOUBLE_CLICK: u64 = 400;
+
+/// The `WHEEL_STEP` constant holds the distance in pixels a touchpad must scroll to count as one
+/// turn of the mouse wheel.  Touchpads report a stream of tiny scrolls, and without this we would
+/// fire an act for every one of them.
+pub const WHEEL_STEP: f64 = 40.0;
+
+/// The `SEQUENCE_TIMEOUT` constant holds the default time allowed between the keys of a
+/// [`Sequence`], in milliseconds.
+pub const SEQUENCE_TIMEOUT: u64 = 1000;
+
+impl Default for Cmd {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(SEQUENCE_TIMEOUT))
+    }
+}
 
 impl Cmd {
-    /// Given an incoming [`event::KeyEvent`] from the [`winit`] crate, the `act` method checks the
-    /// [`HashMap`] in `Self` to determine if the key event maps to an [`Act`] variant.
-    pub fn act(&self, event: &event::KeyEvent) -> Option<Act> {
-        match event.logical_key.as_ref() {
-            keyboard::Key::Named(k) => {
-                let key = format!("{k:?}");
-                if let Some(act) = self.get(&key) {
-                    tracing::trace!("Act detected: {act}");
-                    Some(act.clone())
-                } else {
-                    None
+    /// The `new` method creates a `Cmd` with no bindings, allowing `timeout` between the keys of
+    /// a sequence.
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            root: Node::default(),
+            modes: BTreeMap::new(),
+            macros: BTreeMap::new(),
+            mode: DEFAULT_MODE.to_string(),
+            timeout,
+            pending: Vec::new(),
+            last: None,
+            clicked: None,
+            scrolled: (0.0, 0.0),
+        }
+    }
+
+    /// The `insert` method binds `keys` to `act` in the global layer, see [`Cmd::insert_in`].
+    pub fn insert(&mut self, keys: &Sequence, act: Act) -> Option<Act> {
+        self.root.insert(keys, act)
+    }
+
+    /// The `insert_in` method binds `keys` to `act` in the keymap of `mode`, creating the mode if
+    /// needed.  If `keys` is already bound, is the prefix of another binding, or has a binding as
+    /// a prefix, we leave the keymap alone and return the [`Act`] standing in the way.
+    pub fn insert_in(&mut self, mode: &str, keys: &Sequence, act: Act) -> Option<Act> {
+        self.modes
+            .entry(mode.to_string())
+            .or_default()
+            .insert(keys, act)
+    }
+
+    /// The `bindings` method lists every binding in the global layer, in order of the key
+    /// sequences.
+    pub fn bindings(&self) -> Vec<(Sequence, Act)> {
+        let mut bindings = Vec::new();
+        self.root.collect(&mut Vec::new(), &mut bindings);
+        bindings
+    }
+
+    /// The `bindings_in` method lists the bindings in the keymap of `mode`, not counting the
+    /// global layer.  Returns an empty list for a mode with no keymap.
+    pub fn bindings_in(&self, mode: &str) -> Vec<(Sequence, Act)> {
+        let mut bindings = Vec::new();
+        if let Some(node) = self.modes.get(mode) {
+            node.collect(&mut Vec::new(), &mut bindings);
+        }
+        bindings
+    }
+
+    /// The `to_toml` method writes the keymap back out as TOML, in the same shape `Tardy.toml`
+    /// uses for bindings, so the output can be pasted into the configuration file.  Each act
+    /// gets a single sequence, or a list when it has more than one.  The `switch_mode` bindings
+    /// and the keymap of each mode get tables of their own, and the sequence timeout goes in the
+    /// `[sequences]` table.
+    pub fn to_toml(&self) -> String {
+        let mut doc = Self::to_table(self.bindings());
+        let mut modes = toml::Table::new();
+        for mode in self.mode_names() {
+            let table = Self::to_table(self.bindings_in(&mode));
+            modes.insert(mode, toml::Value::Table(table));
+        }
+        if !modes.is_empty() {
+            doc.insert("modes".to_string(), toml::Value::Table(modes));
+        }
+        let macros = self
+            .macros
+            .iter()
+            .map(|(name, routine)| (name.clone(), routine.to_toml()))
+            .collect::<toml::Table>();
+        if !macros.is_empty() {
+            doc.insert(MACROS_KEY.to_string(), toml::Value::Table(macros));
+        }
+        let mut sequences = toml::Table::new();
+        let timeout = i64::try_from(self.timeout.as_millis()).unwrap_or(i64::MAX);
+        sequences.insert("timeout".to_string(), toml::Value::Integer(timeout));
+        doc.insert("sequences".to_string(), toml::Value::Table(sequences));
+        doc.to_string()
+    }
+
+    /// The `to_table` method gathers `bindings` into a table from the name of each act to its
+    /// keys, with [`Act::SwitchMode`] bindings in a `switch_mode` table by mode, and acts with
+    /// parameters in a `binds` list of tables.
+    fn to_table(bindings: Vec<(Sequence, Act)>) -> toml::Table {
+        let mut acts = BTreeMap::<Act, Vec<toml::Value>>::new();
+        for (keys, act) in bindings {
+            acts.entry(act)
+                .or_default()
+                .push(toml::Value::String(keys.to_string()));
+        }
+        let mut table = toml::Table::new();
+        let mut switch = toml::Table::new();
+        let mut binds = Vec::new();
+        for (act, mut keys) in acts {
+            let value = if keys.len() == 1 {
+                keys.remove(0)
+            } else {
+                toml::Value::Array(keys)
+            };
+            match act {
+                Act::SwitchMode { mode } => {
+                    switch.insert(mode, value);
+                }
+                Act::Macro { name } => {
+                    table.insert(name, value);
+                }
+                act if act.is_plain() => {
+                    table.insert(act.snake(), value);
+                }
+                act => {
+                    if let Ok(toml::Value::Table(mut bind)) = toml::Value::try_from(&act) {
+                        bind.insert("keys".to_string(), value);
+                        binds.push(toml::Value::Table(bind));
+                    }
+                }
+            }
+        }
+        if !binds.is_empty() {
+            table.insert(BINDS_KEY.to_string(), toml::Value::Array(binds));
+        }
+        if !switch.is_empty() {
+            table.insert(
+                Act::SwitchMode {
+                    mode: String::new(),
+                }
+                .snake(),
+                toml::Value::Table(switch),
+            );
+        }
+        table
+    }
+
+    /// The `mode_names` method lists the modes with a keymap, in alphabetical order.
+    pub fn mode_names(&self) -> Vec<String> {
+        self.modes.keys().cloned().collect()
+    }
+
+    /// The `switch` method makes `mode` the current mode, and drops any keys pressed so far.
+    ///
+    /// Will [`Excuse::UnknownMode`] if `mode` has no keymap and is not the [`DEFAULT_MODE`].
+    pub fn switch(&mut self, mode: &str) -> Arrive<()> {
+        if mode != DEFAULT_MODE && !self.modes.contains_key(mode) {
+            return Err(Blame::Excuse(Excuse::UnknownMode(mode.to_string())));
+        }
+        self.cancel();
+        self.mode = mode.to_string();
+        tracing::info!("Mode: {mode}");
+        Ok(())
+    }
+
+    /// The `layers` method returns the keymaps consulted in the current mode, in order: the
+    /// keymap of the mode, if it has one, then the global layer.
+    fn layers(&self) -> Vec<&Node> {
+        self.modes
+            .get(&self.mode)
+            .into_iter()
+            .chain(std::iter::once(&self.root))
+            .collect()
+    }
+
+    /// The `is_empty` method returns `true` if nothing is bound, in any mode.
+    pub fn is_empty(&self) -> bool {
+        self.root.next.is_empty() && self.modes.values().all(|node| node.next.is_empty())
+    }
+
+    /// The `rebind` method makes `keys` the only binding for `act` in the global keymap,
+    /// replacing any old bindings, for use by a [`crate::Capture`].  Bindings in modes stay as
+    /// they are.
+    ///
+    /// Will [`crate::Blame::Excuse`] with [`Excuse::Conflict`] if `keys` would collide with the
+    /// binding of another act, leaving the keymap unchanged.
+    pub fn rebind(&mut self, act: &Act, keys: &Sequence) -> Arrive<()> {
+        let mut root = self.root.clone();
+        root.remove(act);
+        if let Some(other) = root.insert(keys, act.clone()) {
+            return Err(Blame::Excuse(Excuse::Conflict(format!(
+                "{keys} ({}, {})",
+                other.snake(),
+                act.snake()
+            ))));
+        }
+        self.root = root;
+        self.cancel();
+        tracing::info!("Bound {keys} to {}", act.snake());
+        Ok(())
+    }
+
+    /// The `is_pending` method returns `true` in the middle of a sequence, when some keys have
+    /// been pressed and we are waiting for the rest.
+    pub fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// The `cancel` method drops any keys pressed so far, returning to the root of the tree.
+    pub fn cancel(&mut self) {
+        self.pending.clear();
+        self.last = None;
+    }
+
+    /// Given an incoming [`event::KeyEvent`] from the [`winit`] crate, and the state of the
+    /// modifier keys in `mods`, the `act` method builds a [`Chord`] from the logical key using
+    /// [`Chord::from_event`], and another from the physical key using [`Chord::from_physical`],
+    /// and hands them to [`Cmd::press_any`].  The logical chord comes first, so when both forms
+    /// are bound, the logical binding wins.  Last comes [`Chord::any`], unless the key is a
+    /// modifier.
+    pub fn act(&mut self, event: &event::KeyEvent, mods: keyboard::ModifiersState) -> Option<Act> {
+        self.act_key(&event.logical_key, &event.physical_key, mods)
+    }
+
+    /// The `act_key` method does the work of [`Cmd::act`] for the `logical` and `physical` keys
+    /// of an event, apart from the rest of the event.
+    pub fn act_key(
+        &mut self,
+        logical: &keyboard::Key,
+        physical: &keyboard::PhysicalKey,
+        mods: keyboard::ModifiersState,
+    ) -> Option<Act> {
+        let mut chords = Vec::new();
+        if let Some(chord) = Chord::from_key(logical, mods) {
+            tracing::trace!("Chord event: {chord}");
+            chords.extend(chord.unshifted());
+            chords.insert(0, chord);
+        }
+        chords.extend(Chord::from_code(physical, mods));
+        if !Chord::is_modifier_key(logical) {
+            chords.push(Chord::any());
+        }
+        self.press_any(chords)
+    }
+
+    /// The `click` method handles a press of the mouse `button`, with the modifier state in
+    /// `mods`.  A second click of the same button within [`DOUBLE_CLICK`] milliseconds plays the
+    /// double click chord, falling back on the single click chord when no double click is bound.
+    /// The first click of a double click always plays as a single click, since we cannot see the
+    /// future.
+    pub fn click(
+        &mut self,
+        button: event::MouseButton,
+        mods: keyboard::ModifiersState,
+    ) -> Option<Act> {
+        let now = Instant::now();
+        let double = self.clicked.is_some_and(|(last, time)| {
+            last == button && now - time <= Duration::from_millis(DOUBLE_CLICK)
+        });
+        // A double click uses up the first click, so a triple click is a double and a single.
+        self.clicked = if double { None } else { Some((button, now)) };
+        let single = Chord::from_mouse(button, false, mods);
+        tracing::trace!("Click event: {single}");
+        if double {
+            self.press_any(vec![
+                Chord::from_mouse(button, true, mods),
+                single,
+                Chord::any(),
+            ])
+        } else {
+            self.press_any(vec![single, Chord::any()])
+        }
+    }
+
+    /// The `scroll` method handles a turn of the mouse wheel by `delta`, with the modifier state in
+    /// `mods`.  Wheel turns measured in lines play a chord right away.  Touchpad scrolling measured
+    /// in pixels adds up until it passes [`WHEEL_STEP`] in some direction.
+    pub fn scroll(
+        &mut self,
+        delta: event::MouseScrollDelta,
+        mods: keyboard::ModifiersState,
+    ) -> Option<Act> {
+        let (x, y) = match delta {
+            event::MouseScrollDelta::LineDelta(x, y) => (x as f64, y as f64),
+            event::MouseScrollDelta::PixelDelta(position) => {
+                self.scrolled.0 += position.x;
+                self.scrolled.1 += position.y;
+                let (x, y) = self.scrolled;
+                if x.abs() < WHEEL_STEP && y.abs() < WHEEL_STEP {
+                    return None;
                 }
-                // tracing::trace!("Named key not implemented: {k:?}");
-                // None
+                self.scrolled = (0.0, 0.0);
+                (x, y)
             }
-            keyboard::Key::Character(k) => {
-                tracing::trace!("Character event: {k}");
-                if let Some(value) = self.get(k) {
-                    Some(value.clone())
-                } else {
-                    tracing::trace!("Command key not present {k}");
+        };
+        let chord = Chord::from_wheel(x, y, mods)?;
+        tracing::trace!("Wheel event: {chord}");
+        self.press_any(vec![chord, Chord::any()])
+    }
+
+    /// The `lookup` method finds an act using `find`, trying the keymap `overlay` first, if there
+    /// is one, then this keymap.  While the overlay is in the middle of a sequence, this keymap
+    /// does not get a look, so the rest of the sequence reaches the overlay.
+    pub fn lookup<F>(&mut self, overlay: Option<&mut Cmd>, mut find: F) -> Option<Act>
+    where
+        F: FnMut(&mut Cmd) -> Option<Act>,
+    {
+        if let Some(keys) = overlay {
+            if let Some(act) = find(keys) {
+                tracing::trace!("Act found in window keymap.");
+                return Some(act);
+            }
+            if keys.is_pending() {
+                return None;
+            }
+        }
+        find(self)
+    }
+
+    /// The `press` method takes one step down the prefix tree with `chord`, returning the [`Act`]
+    /// at the end of a completed sequence.  If the exact chord leads nowhere, we try again without
+    /// the shift key, see [`Chord::unshifted`].
+    pub fn press(&mut self, chord: Chord) -> Option<Act> {
+        let plain = chord.unshifted();
+        let mut chords = vec![chord];
+        chords.extend(plain);
+        self.press_any(chords)
+    }
+
+    /// The `press_any` method takes one step down the prefix tree with the first of `chords` that
+    /// leads somewhere, returning the [`Act`] at the end of a completed sequence.  The chords are
+    /// alternate readings of the same key press, in order of preference.  The keymap of the current
+    /// mode gets the first look, before the global layer.  When the chords do not continue the
+    /// pending keys, we drop the pending keys and look again from the root, the same as after a
+    /// timeout, so the key press is not swallowed.
+    pub fn press_any(&mut self, chords: Vec<Chord>) -> Option<Act> {
+        let first = chords.first()?.clone();
+        let now = Instant::now();
+        if !self.pending.is_empty() {
+            if self.last.is_some_and(|last| now - last > self.timeout) {
+                tracing::info!(
+                    "Sequence timed out: {}",
+                    Sequence::new(self.pending.clone())
+                );
+                self.cancel();
+            } else if first == Chord::new(keyboard::ModifiersState::empty(), "Escape") {
+                tracing::info!(
+                    "Sequence cancelled: {}",
+                    Sequence::new(self.pending.clone())
+                );
+                self.cancel();
+                return None;
+            }
+        }
+        // Each layer in turn gets a chance to continue the pending keys with one of the chords.
+        let found = self.layers().into_iter().find_map(|layer| {
+            let node = layer.lookup(&self.pending)?;
+            chords
+                .iter()
+                .find_map(|chord| node.next.get(chord).map(|next| (chord.clone(), next)))
+        });
+        let Some((chord, next)) = found else {
+            if self.pending.is_empty() {
+                tracing::trace!("Command key not present {first}");
+                return None;
+            }
+            let mut keys = self.pending.clone();
+            keys.push(first);
+            tracing::info!("No binding for {}", Sequence::new(keys));
+            self.cancel();
+            return self.press_any(chords);
+        };
+        if let Some(act) = &next.act {
+            let act = act.clone();
+            tracing::trace!("Act detected: {act}");
+            self.cancel();
+            return Some(act);
+        }
+        let more = next.next.len();
+        self.pending.push(chord);
+        self.last = Some(now);
+        tracing::info!(
+            "{} ... ({more} possible next keys)",
+            Sequence::new(self.pending.clone())
+        );
+        None
+    }
+
+    /// The `from_config` method reads key bindings from the top level of `config`, returning the
+    /// bindings along with a diagnostic for each problem found, so the caller can report them.
+    ///
+    /// Each key at the top level of `Tardy.toml` names an [`Act`] in snake case, and the value
+    /// holds either a single [`Sequence`] or a list of sequences, so one act can answer to several
+    /// keys:
+    ///
+    /// ```toml
+    /// exit = ["Escape", "Ctrl+x Ctrl+c"]
+    /// new_window = "n"
+    /// ```
+    ///
+    /// Keys whose value is a table, like `[quotes]`, or a list of tables, like `[[imps]]`, are
+    /// sections of the configuration rather than bindings, and we leave them alone, unless the
+    /// key names an act.  Everything else had better be a binding.  The diagnostics are:
+    ///
+    /// * [`crate::Blame::Config`] when the configuration is not a table at all.
+    /// * [`Excuse::UnknownAct`] when the key does not name an act.
+    /// * [`Excuse::Unbound`] when the value is empty, or an empty list.
+    /// * [`Excuse::Binding`] when a value is not a string, like `exit = 1` or `exit = true`, or
+    ///   does not parse as a [`Sequence`].
+    /// * [`Excuse::Conflict`] when a sequence is bound more than once, or is the prefix of
+    ///   another binding.  The first binding wins, and since we visit the keys in alphabetical
+    ///   order, the winner does not change from run to run.
+    /// * [`Excuse::UnknownMode`] when `switch_mode` names a mode with no table under `[modes]`.
+    /// * [`Excuse::UnknownPreset`] when the `keymap` key does not name a [`Preset`].
+    /// * [`Excuse::Parameter`] when an act has parameters out of bounds, see [`Act::validate`].
+    ///
+    /// Acts with parameters go in a `[[binds]]` list of tables, as described in [`Act`].
+    ///
+    /// The `keymap` key is the one top level string that is not a binding.  It names a
+    /// [`Preset`] to start from, and the bindings in the file go on top of it.  An act bound in
+    /// the file drops the keys the preset gave it, and a preset binding that collides with one
+    /// in the file gives way.
+    ///
+    /// The `timeout` comes from the `[sequences]` table, if present.
+    pub fn from_config(config: &config::Config) -> (Self, Vec<Blame>) {
+        let timeout = match config.get::<u64>("sequences.timeout") {
+            Ok(ms) => ms,
+            Err(config::ConfigError::NotFound(_)) => SEQUENCE_TIMEOUT,
+            Err(e) => {
+                tracing::warn!("Could not read sequence timeout: {e}");
+                SEQUENCE_TIMEOUT
+            }
+        };
+        let mut cmds = Self::new(Duration::from_millis(timeout));
+        let mut snags = Vec::new();
+        let mut table = match config.cache.clone().into_table() {
+            Ok(table) => table,
+            Err(e) => {
+                snags.push(e.into());
+                return (cmds, snags);
+            }
+        };
+        let preset = match table.remove(KEYMAP_KEY) {
+            None => None,
+            Some(value) => match value.clone().into_string() {
+                Ok(name) => Preset::from_name(&name).map_err(|e| snags.push(e)).ok(),
+                Err(_) => {
+                    snags.push(Self::bad(KEYMAP_KEY, &value.to_string()));
                     None
                 }
+            },
+        };
+        // Modes are declared by their tables, so we gather the names before reading any
+        // `switch_mode` bindings that refer to them.
+        let mut modes = BTreeMap::new();
+        match table.get("modes").map(|v| v.clone().into_table()) {
+            None => {}
+            Some(Ok(tables)) => {
+                for (name, value) in tables {
+                    match value.clone().into_table() {
+                        Ok(mode) => {
+                            modes.insert(name, mode);
+                        }
+                        Err(_) => {
+                            snags.push(Self::bad(&format!("modes.{name}"), &value.to_string()))
+                        }
+                    }
+                }
+            }
+            Some(Err(_)) => snags.push(Self::bad("modes", &table["modes"].to_string())),
+        }
+        let mut names = modes.keys().cloned().collect::<Vec<String>>();
+        names.push(DEFAULT_MODE.to_string());
+        cmds.macros = Self::read_macros(&table, &mut snags);
+        let macros = cmds.macro_names();
+        Self::bind_table(&mut cmds.root, &table, "", &names, &macros, &mut snags);
+        if let Some(preset) = preset {
+            cmds.layer_preset(preset);
+        }
+        for (name, mode) in modes {
+            let layer = cmds.modes.entry(name.clone()).or_default();
+            let scope = format!("modes.{name}.");
+            Self::bind_table(layer, &mode, &scope, &names, &macros, &mut snags);
+        }
+        if cmds.is_empty() {
+            tracing::trace!("No valid commands detected!");
+        }
+        (cmds, snags)
+    }
+
+    /// The `overlay` method reads a keymap from the table named `key` in `config`, for use as a
+    /// per-window overlay, see [`crate::Lens`].  The overlay shares the sequence timeout of this
+    /// `Cmd`, and its `switch_mode` bindings may refer to the modes of this `Cmd`.  Returns
+    /// [`None`] when the table is missing, along with the diagnostics described in
+    /// [`Cmd::from_config`].
+    pub fn overlay(&self, config: &config::Config, key: &str) -> (Option<Self>, Vec<Blame>) {
+        let mut snags = Vec::new();
+        let table = match config.get_table(key) {
+            Ok(table) => table,
+            Err(config::ConfigError::NotFound(_)) => return (None, snags),
+            Err(e) => {
+                snags.push(e.into());
+                return (None, snags);
+            }
+        };
+        let mut names = self.mode_names();
+        names.push(DEFAULT_MODE.to_string());
+        let macros = self.macro_names();
+        let mut overlay = Self::new(self.timeout);
+        overlay.macros = self.macros.clone();
+        let scope = format!("{key}.");
+        Self::bind_table(
+            &mut overlay.root,
+            &table,
+            &scope,
+            &names,
+            &macros,
+            &mut snags,
+        );
+        (Some(overlay), snags)
+    }
+
+    /// The `read_macros` method reads the `[macros]` table from the top level `table` of the
+    /// configuration, adding a diagnostic to `snags` for each problem, see
+    /// [`Routine::from_value`].  A macro named after an act could never be bound, since the act
+    /// wins, so we reject it with [`Excuse::Binding`].
+    fn read_macros(
+        table: &config::Map<String, config::Value>,
+        snags: &mut Vec<Blame>,
+    ) -> BTreeMap<String, Routine> {
+        let mut macros = BTreeMap::new();
+        let Some(value) = table.get(MACROS_KEY) else {
+            return macros;
+        };
+        let tables = match value.clone().into_table() {
+            Ok(tables) => tables,
+            Err(_) => {
+                snags.push(Self::bad(MACROS_KEY, &value.to_string()));
+                return macros;
+            }
+        };
+        for (name, steps) in tables {
+            if Act::iter().any(|act| act.snake() == name) {
+                snags.push(Self::bad(
+                    &format!("{MACROS_KEY}.{name}"),
+                    "a macro may not share the name of an act",
+                ));
+                continue;
+            }
+            let routine = Routine::from_value(&name, steps, snags);
+            macros.insert(name, routine);
+        }
+        macros
+    }
+
+    /// The `macro_names` method lists the names of the macros, in alphabetical order.
+    pub fn macro_names(&self) -> Vec<String> {
+        self.macros.keys().cloned().collect()
+    }
+
+    /// The `bind_table` method reads the bindings in `table` into the keymap at `layer`, adding a
+    /// diagnostic to `snags` for each problem, as described in [`Cmd::from_config`].  The `scope`
+    /// goes in front of each key in diagnostics, so the user can tell which table it came from,
+    /// `modes` holds the names of the modes that `switch_mode` may refer to, and `macros` holds
+    /// the names of the macros that may be bound alongside the acts.
+    fn bind_table(
+        layer: &mut Node,
+        table: &config::Map<String, config::Value>,
+        scope: &str,
+        modes: &[String],
+        macros: &[String],
+        snags: &mut Vec<Blame>,
+    ) {
+        let mut keys = table.keys().cloned().collect::<Vec<String>>();
+        keys.sort();
+        for key in keys {
+            let value = table[&key].clone();
+            if key == BINDS_KEY {
+                let name = format!("{scope}{key}");
+                Self::bind_tables(layer, value, &name, modes, macros, snags);
+                continue;
+            }
+            let act = Act::iter().find(|a| a.snake() == key).or_else(|| {
+                macros
+                    .contains(&key)
+                    .then(|| Act::Macro { name: key.clone() })
+            });
+            let Some(act) = act else {
+                if !Self::is_section(&value) {
+                    snags.push(Blame::Excuse(Excuse::UnknownAct(format!("{scope}{key}"))));
+                }
+                continue;
+            };
+            tracing::trace!("Command detected: {act}");
+            if let Act::SwitchMode { .. } = act {
+                let targets = match value.clone().into_table() {
+                    Ok(targets) => targets,
+                    Err(_) => {
+                        snags.push(Self::bad(&format!("{scope}{key}"), &value.to_string()));
+                        continue;
+                    }
+                };
+                let mut targets = targets
+                    .into_iter()
+                    .collect::<Vec<(String, config::Value)>>();
+                targets.sort_by(|a, b| a.0.cmp(&b.0));
+                for (mode, value) in targets {
+                    if !modes.contains(&mode) {
+                        snags.push(Blame::Excuse(Excuse::UnknownMode(mode)));
+                        continue;
+                    }
+                    let name = format!("{scope}{key}.{mode}");
+                    Self::bind_values(layer, &name, value, Act::SwitchMode { mode }, snags);
+                }
+            } else if let Err(e) = act.validate() {
+                // Acts like `resize_window` have no sensible parameters to go without.
+                snags.push(e);
+            } else {
+                Self::bind_values(layer, &format!("{scope}{key}"), value, act, snags);
+            }
+        }
+    }
+
+    /// The `bind_tables` method reads a `[[binds]]` list of tables into the keymap at `layer`.
+    /// Each table holds the `keys` to bind, and the act to bind them to, tagged by the `act`
+    /// field, with any parameters alongside.  The `name` identifies the list in diagnostics, with
+    /// the position of the table tacked on.  Besides the usual diagnostics from
+    /// [`Cmd::from_config`], a table earns [`Excuse::Unbound`] when it has no `keys`,
+    /// [`Excuse::Binding`] when the rest of the table does not make an [`Act`], and
+    /// [`Excuse::Parameter`] when the act fails [`Act::validate`].
+    fn bind_tables(
+        layer: &mut Node,
+        value: config::Value,
+        name: &str,
+        modes: &[String],
+        macros: &[String],
+        snags: &mut Vec<Blame>,
+    ) {
+        let tables = match value.clone().into_array() {
+            Ok(tables) => tables,
+            Err(_) => {
+                snags.push(Self::bad(name, &value.to_string()));
+                return;
+            }
+        };
+        for (idx, table) in tables.into_iter().enumerate() {
+            let name = format!("{name}[{idx}]");
+            let mut fields = match table.clone().into_table() {
+                Ok(fields) => fields,
+                Err(_) => {
+                    snags.push(Self::bad(&name, &table.to_string()));
+                    continue;
+                }
+            };
+            let Some(keys) = fields.remove("keys") else {
+                snags.push(Blame::Excuse(Excuse::Unbound(name)));
+                continue;
+            };
+            let fields = config::Value::new(None, config::ValueKind::Table(fields));
+            let act = match fields.try_deserialize::<Act>() {
+                Ok(act) => act,
+                Err(e) => {
+                    snags.push(Self::bad(&name, &e.to_string()));
+                    continue;
+                }
+            };
+            if let Err(e) = act.validate() {
+                snags.push(e);
+                continue;
+            }
+            match &act {
+                Act::SwitchMode { mode } if !modes.contains(mode) => {
+                    snags.push(Blame::Excuse(Excuse::UnknownMode(mode.clone())));
+                    continue;
+                }
+                Act::Macro { name } if !macros.contains(name) => {
+                    snags.push(Blame::Excuse(Excuse::UnknownMacro(name.clone())));
+                    continue;
+                }
+                _ => {}
+            }
+            tracing::trace!("Command detected: {act:?}");
+            Self::bind_values(layer, &name, keys, act, snags);
+        }
+    }
+
+    /// The `bind_values` method binds each sequence in `value`, a string or list of strings, to
+    /// `act` in the keymap at `layer`.  The `name` identifies the binding in diagnostics.
+    fn bind_values(
+        layer: &mut Node,
+        name: &str,
+        value: config::Value,
+        act: Act,
+        snags: &mut Vec<Blame>,
+    ) {
+        let values = match value.kind {
+            config::ValueKind::Array(values) => values,
+            _ => vec![value],
+        };
+        let mut bound = false;
+        for value in values {
+            let text = match &value.kind {
+                config::ValueKind::String(text) => text,
+                _ => {
+                    bound = true;
+                    snags.push(Self::bad(name, &value.to_string()));
+                    continue;
+                }
+            };
+            if text.trim().is_empty() {
+                continue;
+            }
+            bound = true;
+            let keys = match Sequence::from_str(text) {
+                Ok(keys) => keys,
+                Err(_) => {
+                    snags.push(Self::bad(name, text));
+                    continue;
+                }
+            };
+            if let Some(other) = layer.insert(&keys, act.clone()) {
+                snags.push(Blame::Excuse(Excuse::Conflict(format!(
+                    "{keys} ({}, {name})",
+                    other.snake()
+                ))));
             }
-            other => {
-                tracing::trace!("Unrecognized key event: {other:?}");
-                None
+        }
+        if !bound {
+            snags.push(Blame::Excuse(Excuse::Unbound(name.to_string())));
+        }
+    }
+
+    /// The `bad` method describes a `value` for `key` that cannot be a binding.
+    fn bad(key: &str, value: &str) -> Blame {
+        Blame::Excuse(Excuse::Binding {
+            key: key.to_string(),
+            value: value.to_string(),
+        })
+    }
+
+    /// The `keymap` method returns the default bindings, from [`DEFAULT_KEYMAP`].  We fall back
+    /// on these bindings when `Tardy.toml` is missing, or has problems with its bindings.
+    pub fn keymap() -> Self {
+        Self::preset(Preset::Default)
+    }
+
+    /// The `preset` method returns a keymap holding the bindings of `preset` and nothing else.
+    pub fn preset(preset: Preset) -> Self {
+        let mut cmds = Self::default();
+        cmds.layer_preset(preset);
+        cmds
+    }
+
+    /// The `layer_preset` method slips the bindings of `preset` underneath the global bindings
+    /// already in the keymap.  An act with bindings of its own keeps them, and ignores the
+    /// preset.  A preset binding that collides with a binding already in place loses quietly,
+    /// since the user bound that key on purpose.
+    fn layer_preset(&mut self, preset: Preset) {
+        let bound = self
+            .bindings()
+            .into_iter()
+            .map(|(_, act)| act)
+            .collect::<Vec<Act>>();
+        for (name, keys) in preset.bindings() {
+            let act = Act::iter().find(|a| a.snake() == *name);
+            if let (Some(act), Ok(keys)) = (act, Sequence::from_str(keys)) {
+                if bound.contains(&act) {
+                    continue;
+                }
+                if let Some(other) = self.root.insert(&keys, act.clone()) {
+                    tracing::debug!(
+                        "Preset {} binds {keys} to {}, but it belongs to {}.",
+                        preset.snake(),
+                        act.snake(),
+                        other.snake()
+                    );
+                }
             }
         }
     }
+
+    /// The `is_fatal` method returns `true` for the diagnostics from [`Cmd::from_config`] that
+    /// throw out the whole keymap: a configuration that is not a table, and a value that is not a
+    /// binding at all, see [`Excuse::Binding`].
+    pub fn is_fatal(snag: &Blame) -> bool {
+        matches!(snag, Blame::Config | Blame::Excuse(Excuse::Binding { .. }))
+    }
+
+    /// The `is_section` method returns `true` if `value` is a table, or a non-empty list of
+    /// tables, marking a section of the configuration rather than a key binding.
+    fn is_section(value: &config::Value) -> bool {
+        match &value.kind {
+            config::ValueKind::Table(_) => true,
+            config::ValueKind::Array(values) => values
+                .first()
+                .is_some_and(|v| matches!(v.kind, config::ValueKind::Table(_))),
+            _ => false,
+        }
+    }
 }
 
-/// Here we rely on the [`strum`] and [`strum_macros`] crates to generate an iterator method over the
-/// variants of [`Act`].  For each variant, we check to see if the [`config::Config`] passed in by the `config` argument  contains the snake
-/// case version of the variant name as a key.  When a key is present, the method inserts a new
-/// entry into [`HashMap`] in `Self` using the `config` value as a key, and the corresponding [`Act`] variant as the value.
-///
 /// The table in the [`config::Config`] has [`String`] representations of [`Act`] variants as keys
 /// and keyboard characters as values.  We need the reverse, where the keyboard character enetered by the
-/// user is the key, and the triggered [`Act`] is the value, so we create a new [`HashMap`] with
+/// user is the key, and the triggered [`Act`] is the value, so we build a prefix tree with
 /// this inverse relationship, stored in the `Cmd` struct.
-impl From<&config::Config> for Cmd {
-    fn from(config: &config::Config) -> Self {
-        let mut cmds = HashMap::new();
-        let table = config.cache.clone().into_table().unwrap();
-        Act::iter()
-            .map(|a| {
-                let key = a.snake();
-                if let Some(entry) = table.get(&key) {
-                    tracing::trace!("Command detected: {a}");
-                    let value = entry.clone().into_string().unwrap();
-                    cmds.insert(value, a.clone());
-                }
-            })
-            .for_each(drop);
-        if cmds.is_empty() {
-            tracing::trace!("No valid commands detected!");
+///
+/// This used to be a `From` implementation, which unwrapped its way through the config and
+/// panicked at startup over a stray number in `Tardy.toml`.  Now the work happens in
+/// [`Cmd::from_config`], and here we fail only on the problems that used to panic, see
+/// [`Cmd::is_fatal`].  Then the error holds every diagnostic, one for each problem, so the user
+/// can fix them all in one go.  Lesser problems, like a misspelled act or a key bound twice, cost
+/// only the binding in question, so we report them as warnings and keep the rest of the keymap.
+impl TryFrom<&config::Config> for Cmd {
+    type Error = Vec<Blame>;
+
+    fn try_from(config: &config::Config) -> Result<Self, Self::Error> {
+        let (cmds, snags) = Self::from_config(config);
+        if snags.iter().any(Self::is_fatal) {
+            return Err(snags);
+        }
+        for snag in snags {
+            tracing::warn!("Bad binding: {snag}");
+        }
+        Ok(cmds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cmd(src: &str) -> (Cmd, Vec<Blame>) {
+        let config = config::Config::builder()
+            .add_source(config::File::from_str(src, config::FileFormat::Toml))
+            .build()
+            .unwrap();
+        Cmd::from_config(&config)
+    }
+
+    fn press(cmd: &mut Cmd, text: &str) -> Option<Act> {
+        cmd.press(text.parse().unwrap())
+    }
+
+    #[test]
+    fn shift_letter_stays_unbound() {
+        let (mut cmd, _) = cmd("new_window = \"n\"");
+        assert_eq!(press(&mut cmd, "Shift+n"), None);
+        assert_eq!(press(&mut cmd, "n"), Some(Act::NewWindow { count: None }));
+    }
+
+    #[test]
+    fn shift_symbol_falls_back() {
+        let (mut cmd, _) = cmd("upvote = \"+\"");
+        assert_eq!(press(&mut cmd, "Shift++"), Some(Act::Upvote));
+    }
+
+    #[test]
+    fn lesser_snags_keep_the_keymap() {
+        let config = config::Config::builder()
+            .add_source(config::File::from_str(
+                "exti = \"q\"\nexit = [\"Escape\", \"Escape\"]\nnew_window = \"\"",
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .unwrap();
+        let mut cmd = Cmd::try_from(&config).unwrap();
+        assert_eq!(press(&mut cmd, "Escape"), Some(Act::Exit));
+    }
+
+    #[test]
+    fn bad_values_throw_out_the_keymap() {
+        let config = config::Config::builder()
+            .add_source(config::File::from_str(
+                "exit = 1\nnew_window = \"n\"",
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .unwrap();
+        let snags = Cmd::try_from(&config).unwrap_err();
+        assert!(snags.iter().any(Cmd::is_fatal), "{snags:?}");
+    }
+
+    fn keys_for(cmd: &Cmd, act: &Act) -> Vec<String> {
+        cmd.bindings()
+            .into_iter()
+            .filter(|(_, bound)| bound == act)
+            .map(|(keys, _)| keys.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn user_binding_replaces_preset_keys() {
+        let (cmd, snags) = cmd("keymap = \"vim\"\nnew_window = \"n\"");
+        assert!(snags.is_empty(), "{snags:?}");
+        let new_window = Act::NewWindow { count: None };
+        assert_eq!(keys_for(&cmd, &new_window), vec!["n"]);
+        assert_eq!(keys_for(&cmd, &Act::CloseWindow), vec!["Ctrl+w c"]);
+    }
+
+    #[test]
+    fn user_key_beats_preset() {
+        let (mut cmd, snags) = cmd("keymap = \"vim\"\nshow_help = \"Ctrl+a\"");
+        assert!(snags.is_empty(), "{snags:?}");
+        assert_eq!(press(&mut cmd, "Ctrl+a"), Some(Act::ShowHelp));
+        assert!(keys_for(&cmd, &Act::Upvote).is_empty());
+        assert_eq!(keys_for(&cmd, &Act::ShowHelp), vec!["Ctrl+a"]);
+    }
+
+    #[test]
+    fn unknown_preset_is_reported() {
+        let (cmd, snags) = cmd("keymap = \"nano\"\nexit = \"q\"");
+        assert_eq!(
+            snags,
+            vec![Blame::Excuse(Excuse::UnknownPreset("nano".to_string()))]
+        );
+        assert_eq!(cmd.bindings().len(), 1);
+    }
+
+    #[test]
+    fn rebind_replaces_old_keys() {
+        let (mut cmd, _) = cmd("exit = \"Escape\"\nnew_window = [\"n\", \"w\"]");
+        let new_window = Act::NewWindow { count: None };
+        cmd.rebind(&new_window, &"g g".parse().unwrap()).unwrap();
+        assert_eq!(keys_for(&cmd, &new_window), vec!["g g"]);
+        assert_eq!(press(&mut cmd, "n"), None);
+    }
+
+    #[test]
+    fn rebind_conflict_leaves_keymap() {
+        let (mut cmd, _) = cmd("exit = \"Escape\"\nnew_window = \"n\"");
+        let before = cmd.bindings();
+        let new_window = Act::NewWindow { count: None };
+        for keys in ["Escape", "Escape x"] {
+            let result = cmd.rebind(&new_window, &keys.parse().unwrap());
+            assert!(
+                matches!(result, Err(Blame::Excuse(Excuse::Conflict(_)))),
+                "{keys}: {result:?}"
+            );
+            assert_eq!(cmd.bindings(), before);
+        }
+    }
+
+    const MODES: &str = "exit = \"x\"\nnew_window = \"n\"\n\
+                         [switch_mode]\nwindow = \"w\"\n\
+                         [modes.window]\nclose_window = \"x\"\n\
+                         [modes.window.switch_mode]\nnormal = \"Escape\"";
+
+    #[test]
+    fn mode_binding_beats_global() {
+        let (mut cmd, snags) = cmd(MODES);
+        assert!(snags.is_empty(), "{snags:?}");
+        let window = Act::SwitchMode {
+            mode: "window".to_string(),
+        };
+        assert_eq!(press(&mut cmd, "w"), Some(window));
+        cmd.switch("window").unwrap();
+        assert_eq!(press(&mut cmd, "x"), Some(Act::CloseWindow));
+        let normal = Act::SwitchMode {
+            mode: DEFAULT_MODE.to_string(),
+        };
+        assert_eq!(press(&mut cmd, "Escape"), Some(normal));
+        cmd.switch(DEFAULT_MODE).unwrap();
+        assert_eq!(press(&mut cmd, "x"), Some(Act::Exit));
+    }
+
+    #[test]
+    fn mode_falls_through_to_global() {
+        let (mut cmd, _) = cmd(MODES);
+        cmd.switch("window").unwrap();
+        assert_eq!(press(&mut cmd, "n"), Some(Act::NewWindow { count: None }));
+        let window = Act::SwitchMode {
+            mode: "window".to_string(),
+        };
+        assert_eq!(press(&mut cmd, "w"), Some(window));
+        assert_eq!(press(&mut cmd, "q"), None);
+    }
+
+    #[test]
+    fn unknown_mode_is_reported() {
+        let (mut cmd, snags) = cmd("[switch_mode]\nnowhere = \"w\"");
+        assert_eq!(
+            snags,
+            vec![Blame::Excuse(Excuse::UnknownMode("nowhere".to_string()))]
+        );
+        assert_eq!(
+            cmd.switch("nowhere"),
+            Err(Blame::Excuse(Excuse::UnknownMode("nowhere".to_string())))
+        );
+        assert_eq!(cmd.mode(), DEFAULT_MODE);
+    }
+
+    #[test]
+    fn switch_cancels_pending_keys() {
+        let (mut cmd, _) = cmd("exit = \"g g\"\n[modes.window]\nclose_window = \"x\"");
+        assert_eq!(press(&mut cmd, "g"), None);
+        assert!(cmd.is_pending());
+        cmd.switch("window").unwrap();
+        assert!(!cmd.is_pending());
+        assert_eq!(press(&mut cmd, "g"), None);
+        assert!(cmd.is_pending());
+    }
+
+    fn click(cmd: &mut Cmd, button: event::MouseButton) -> Option<Act> {
+        cmd.click(button, keyboard::ModifiersState::empty())
+    }
+
+    fn pixels(cmd: &mut Cmd, y: f64) -> Option<Act> {
+        let delta = event::MouseScrollDelta::PixelDelta(winit::dpi::PhysicalPosition::new(0.0, y));
+        cmd.scroll(delta, keyboard::ModifiersState::empty())
+    }
+
+    #[test]
+    fn triple_click_is_double_and_single() {
+        let (mut cmd, _) = cmd("close_window = \"MouseLeft\"\nnew_window = \"DoubleMouseLeft\"");
+        let left = event::MouseButton::Left;
+        let double = Some(Act::NewWindow { count: None });
+        assert_eq!(click(&mut cmd, left), Some(Act::CloseWindow));
+        assert_eq!(click(&mut cmd, left), double);
+        assert_eq!(click(&mut cmd, left), Some(Act::CloseWindow));
+        assert_eq!(click(&mut cmd, left), double);
+    }
+
+    #[test]
+    fn slow_or_mixed_clicks_are_single() {
+        let (mut cmd, _) = cmd("close_window = \"MouseLeft\"\nnew_window = \"DoubleMouseLeft\"");
+        let left = event::MouseButton::Left;
+        assert_eq!(click(&mut cmd, left), Some(Act::CloseWindow));
+        let late = Duration::from_millis(DOUBLE_CLICK + 1);
+        cmd.clicked = cmd.clicked.map(|(button, time)| (button, time - late));
+        assert_eq!(click(&mut cmd, left), Some(Act::CloseWindow));
+        assert_eq!(click(&mut cmd, event::MouseButton::Right), None);
+        assert_eq!(click(&mut cmd, left), Some(Act::CloseWindow));
+    }
+
+    #[test]
+    fn unbound_double_click_plays_single() {
+        let (mut cmd, _) = cmd("close_window = \"MouseLeft\"");
+        let left = event::MouseButton::Left;
+        assert_eq!(click(&mut cmd, left), Some(Act::CloseWindow));
+        assert_eq!(click(&mut cmd, left), Some(Act::CloseWindow));
+    }
+
+    #[test]
+    fn wheel_lines_play_at_once() {
+        let (mut cmd, _) = cmd("upvote = \"WheelUp\"\ndownvote = \"Ctrl+WheelDown\"");
+        let lines = event::MouseScrollDelta::LineDelta(0.0, 1.0);
+        let none = keyboard::ModifiersState::empty();
+        assert_eq!(cmd.scroll(lines, none), Some(Act::Upvote));
+        let lines = event::MouseScrollDelta::LineDelta(0.0, -1.0);
+        let ctrl = keyboard::ModifiersState::CONTROL;
+        assert_eq!(cmd.scroll(lines, ctrl), Some(Act::Downvote));
+    }
+
+    #[test]
+    fn wheel_pixels_add_up_to_a_step() {
+        let (mut cmd, _) = cmd("upvote = \"WheelUp\"\ndownvote = \"WheelDown\"");
+        let third = WHEEL_STEP / 3.0 + 1.0;
+        assert_eq!(pixels(&mut cmd, third), None);
+        assert_eq!(pixels(&mut cmd, third), None);
+        assert_eq!(pixels(&mut cmd, third), Some(Act::Upvote));
+        assert_eq!(pixels(&mut cmd, third), None);
+        assert_eq!(pixels(&mut cmd, -third), None);
+        assert_eq!(pixels(&mut cmd, -WHEEL_STEP), Some(Act::Downvote));
+    }
+
+    fn key(cmd: &mut Cmd, key: keyboard::Key) -> Option<Act> {
+        let code = keyboard::PhysicalKey::Code(keyboard::KeyCode::KeyQ);
+        cmd.act_key(&key, &code, keyboard::ModifiersState::empty())
+    }
+
+    #[test]
+    fn any_catches_unbound_keys() {
+        let (mut cmd, snags) = cmd("exit = \"Escape\"\nclose_window = \"Any\"");
+        assert!(snags.is_empty(), "{snags:?}");
+        let escape = keyboard::Key::Named(keyboard::NamedKey::Escape);
+        assert_eq!(key(&mut cmd, escape), Some(Act::Exit));
+        let q = keyboard::Key::Character("q".into());
+        assert_eq!(key(&mut cmd, q), Some(Act::CloseWindow));
+        let left = event::MouseButton::Left;
+        assert_eq!(click(&mut cmd, left), Some(Act::CloseWindow));
+        for modifier in [keyboard::NamedKey::Shift, keyboard::NamedKey::Control] {
+            assert_eq!(key(&mut cmd, keyboard::Key::Named(modifier)), None);
         }
-        Self::new(cmds)
+    }
+
+    #[test]
+    fn overlay_beats_global() {
+        let src = "exit = \"x\"\nnew_window = \"n\"\n\
+                   [imp_window]\nclose_window = [\"x\", \"g g\"]";
+        let config = config::Config::builder()
+            .add_source(config::File::from_str(src, config::FileFormat::Toml))
+            .build()
+            .unwrap();
+        let (mut global, _) = Cmd::from_config(&config);
+        let (overlay, snags) = global.overlay(&config, "imp_window");
+        assert!(snags.is_empty(), "{snags:?}");
+        let mut overlay = overlay.unwrap();
+        let mut find = |keys: &str| {
+            let chord = keys.parse::<Chord>().unwrap();
+            global.lookup(Some(&mut overlay), |cmd| cmd.press(chord.clone()))
+        };
+        assert_eq!(find("x"), Some(Act::CloseWindow));
+        assert_eq!(find("n"), Some(Act::NewWindow { count: None }));
+        assert_eq!(find("g"), None);
+        assert_eq!(find("g"), Some(Act::CloseWindow));
+        let (missing, _) = global.overlay(&config, "nowhere");
+        assert!(missing.is_none());
+        assert_eq!(
+            global.lookup(None, |cmd| cmd.press("x".parse().unwrap())),
+            Some(Act::Exit)
+        );
+    }
+
+    #[test]
+    fn prefix_conflict_is_reported() {
+        let (_, snags) = cmd("exit = \"g\"\nnew_window = \"g g\"");
+        assert!(
+            snags
+                .iter()
+                .any(|snag| matches!(snag, Blame::Excuse(Excuse::Conflict(_)))),
+            "{snags:?}"
+        );
+    }
+
+    #[test]
+    fn sequence_completes() {
+        let (mut cmd, snags) = cmd("exit = \"g g\"");
+        assert!(snags.is_empty(), "{snags:?}");
+        assert_eq!(press(&mut cmd, "g"), None);
+        assert_eq!(press(&mut cmd, "g"), Some(Act::Exit));
+    }
+
+    #[test]
+    fn escape_cancels_sequence() {
+        let (mut cmd, _) = cmd("exit = [\"Escape\", \"g g\"]");
+        assert_eq!(press(&mut cmd, "g"), None);
+        assert_eq!(press(&mut cmd, "Escape"), None);
+        assert_eq!(press(&mut cmd, "g"), None);
+        assert_eq!(press(&mut cmd, "Escape"), None);
+        assert_eq!(press(&mut cmd, "Escape"), Some(Act::Exit));
+    }
+
+    #[test]
+    fn timeout_cancels_sequence() {
+        let (mut cmd, _) = cmd("exit = \"g g\"\n[sequences]\ntimeout = 1000");
+        assert_eq!(press(&mut cmd, "g"), None);
+        cmd.last = cmd.last.map(|last| last - Duration::from_secs(2));
+        assert_eq!(press(&mut cmd, "g"), None);
+        assert_eq!(press(&mut cmd, "g"), Some(Act::Exit));
+    }
+
+    #[test]
+    fn broken_sequence_retries_from_root() {
+        let (mut cmd, _) = cmd("exit = \"g g\"\nnew_window = \"x\"");
+        assert_eq!(press(&mut cmd, "g"), None);
+        assert_eq!(press(&mut cmd, "x"), Some(Act::NewWindow { count: None }));
+        assert_eq!(press(&mut cmd, "g"), None);
+        assert_eq!(press(&mut cmd, "g"), Some(Act::Exit));
     }
 }
@@ -0,0 +1,113 @@
+use crate::{Act, App, Arrive, Meddle};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+use winit::{event_loop, window};
+
+/// The `dispatch` module holds the [`Dispatcher`] struct, which hands each [`Act`] to the handler
+/// registered under its name, and the [`Deed`] struct, which carries an act to its handler.
+///
+/// # Dispatching Acts
+///
+/// The [`App`] used to answer acts with one big `match` in [`App::act`], and the imps took a
+/// separate road through `user_event`, with a `match` of their own.  Two roads meant two sets of
+/// logging, two sets of error handling, and no way for anyone using the library to add an act of
+/// their own without editing both.
+///
+/// Now every act goes through [`App::dispatch`], whether it comes from a key, a macro or an imp.
+/// The act travels as a [`Deed`], along with its [`Origin`] and the window it is aimed at, and
+/// the `Dispatcher` looks up the [`Handler`] registered under [`Act::name`].  The built-in acts
+/// register their handlers when the [`App`] starts, and [`App::register`] adds new handlers or
+/// replaces the built-in ones.
+///
+/// A library user binds their own act to keys as an [`Act::Custom`] in a `[[binds]]` table:
+///
+/// ```toml
+/// [[binds]]
+/// keys = "Ctrl+h"
+/// act = "custom"
+/// name = "say_hello"
+/// ```
+///
+/// Then registers a handler called `say_hello` before running the event loop.  An act with no
+/// handler earns a warning, and nothing else happens.
+#[derive(Clone, Default)]
+pub struct Dispatcher {
+    handlers: BTreeMap<String, Handler>,
+}
+
+/// The `Handler` type holds a function answering a [`Deed`], with access to the [`App`] and the
+/// active event loop.
+pub type Handler = Arc<
+    dyn Fn(&mut App, &Deed, &event_loop::ActiveEventLoop) -> Arrive<()> + Send + Sync + 'static,
+>;
+
+/// The `Deed` struct holds an act on its way to a [`Handler`].
+///
+/// * The `act` field holds the [`Act`] to perform.
+/// * The `origin` field holds the [`Origin`] of the act.
+/// * The `window` field holds the window the act is aimed at, if any.  Imps aim at no window in
+///   particular.
+#[derive(Debug, Clone, derive_new::new, derive_getters::Getters)]
+pub struct Deed {
+    act: Act,
+    origin: Origin,
+    window: Option<window::WindowId>,
+}
+
+/// The `Origin` enum describes where a [`Deed`] came from.
+#[derive(Debug, Clone, derive_more::Display)]
+pub enum Origin {
+    /// The `User` variant marks an act bound to a key, click or turn of the wheel.
+    #[display("user")]
+    User,
+    /// The `Routine` variant marks an act played by a [`crate::Routine`].
+    #[display("routine")]
+    Routine,
+    /// The `Imp` variant marks an act sent by an [`crate::Imp`], along with the [`Meddle`] it
+    /// came in, which holds the frame and title for a new window.
+    #[display("imp {}", _0.title())]
+    Imp(Meddle),
+}
+
+impl Dispatcher {
+    /// The `register` method registers `handler` under `name`, replacing any handler already
+    /// registered under that name.
+    pub fn register<F>(&mut self, name: &str, handler: F)
+    where
+        F: Fn(&mut App, &Deed, &event_loop::ActiveEventLoop) -> Arrive<()> + Send + Sync + 'static,
+    {
+        if self
+            .handlers
+            .insert(name.to_string(), Arc::new(handler))
+            .is_some()
+        {
+            tracing::debug!("Replaced the handler for {name}.");
+        }
+    }
+
+    /// The `get` method returns the handler registered under `name`, if any.
+    pub fn get(&self, name: &str) -> Option<Handler> {
+        self.handlers.get(name).cloned()
+    }
+
+    /// The `names` method lists the names with a registered handler, in alphabetical order.
+    pub fn names(&self) -> Vec<String> {
+        self.handlers.keys().cloned().collect()
+    }
+}
+
+impl fmt::Debug for Dispatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Dispatcher")
+            .field("handlers", &self.names())
+            .finish()
+    }
+}
+
+impl Origin {
+    /// The `is_imp` method returns `true` if an imp sent the act.
+    pub fn is_imp(&self) -> bool {
+        matches!(self, Self::Imp(_))
+    }
+}
@@ -1,10 +1,13 @@
-use crate::{Act, Arrive, Frame, FRAMES};
+use crate::{
+    Act, Arrive, Blame, Excuse, Frame, Ledger, Lore, Picker, Temper, Tempers, Tome, FRAMES,
+};
 use convert_case::Casing;
+use std::collections::HashSet;
 use std::time::Duration;
-use std::{fs, path};
-use tokio::sync::{mpsc, oneshot};
+use std::{fs, io, path};
+use tokio::sync::{mpsc, oneshot, watch};
 use tokio::time;
-use winit::event_loop;
+use winit::{event_loop, window};
 
 /// The `imp` module holds data types and methods for causing hijinks.
 ///
@@ -32,19 +35,60 @@ use winit::event_loop;
 /// including a oneshot transmitter in the variant so the application has a way to send the frames
 /// back.  There is a fairly long story behind this buried in the docs for the [`Imp::filch`]
 /// method.
-#[derive(Debug, derive_new::new, derive_getters::Getters)]
+#[derive(Debug, derive_getters::Getters)]
 pub struct Imp {
+    /// Receives the latest corpus from the Imp King.
+    corpus: watch::Receiver<Quotes>,
+    /// Receives the latest ratings, sent by the application after each vote.
+    ratings: watch::Receiver<Ledger>,
     /// Holds frames for new windows.
     frames: Vec<Frame>,
+    /// Number of the imp, unique among the minions of an [`ImpKing`].
+    id: usize,
     /// Name of imp, not guaranteed to be unique.
     name: String,
+    /// Picks the next quote to vandalize with.
+    picker: Picker,
     /// Inspirational quotes used to spam the console.
     quotes: Quotes,
+    /// Behavior profile of the imp.
+    temper: Temper,
     /// Send hijinks to the Imp King.
     tx: mpsc::Sender<Hijinks>,
 }
 
 impl Imp {
+    /// The `new` method creates an `Imp` with the number `id` and the frames in `frames`, who
+    /// sends [`Hijinks`] through `tx`, learns quotes from `corpus` and hears about new ratings
+    /// from `ratings`.  The `temper` shapes the
+    /// imp: it may replace `name`, it filters the quotes from `corpus` using [`Temper::quotes`],
+    /// and it picks the [`crate::Pick`] strategy for a new [`Picker`].
+    pub fn new(
+        corpus: watch::Receiver<Quotes>,
+        ratings: watch::Receiver<Ledger>,
+        frames: Vec<Frame>,
+        id: usize,
+        name: String,
+        temper: Temper,
+        tx: mpsc::Sender<Hijinks>,
+    ) -> Self {
+        let name = temper.name().clone().unwrap_or(name);
+        let picker = Picker::new(temper.pick().clone());
+        let quotes = temper.quotes(&corpus.borrow());
+        tracing::trace!("{name} knows {} quotes.", quotes.len());
+        Self {
+            corpus,
+            ratings,
+            frames,
+            id,
+            name,
+            picker,
+            quotes,
+            temper,
+            tx,
+        }
+    }
+
     /// The `pause` method calls [`time::sleep`] from the [`tokio`] crate.  This seems to be the
     /// de-facto way to demonstrate asynchronicity when otherwise the operation would complete too
     /// quickly.  In this case, we want the occassional interuption of the user's workflow to be
@@ -74,7 +118,11 @@ impl Imp {
     pub async fn instigate(&mut self) -> Arrive<()> {
         let frame = self.frames.pop();
         if frame.is_some() {
-            let meddle = Meddle::new(Act::NewWindow, frame, format!("{}'s Window", self.name()));
+            let meddle = Meddle::new(
+                Act::NewWindow { count: None },
+                frame,
+                format!("{}'s Window", self.name()),
+            );
             tracing::trace!("Hijinks instigated.");
             self.tx.send(Hijinks::Meddle(meddle)).await?;
         } else {
@@ -188,41 +236,65 @@ impl Imp {
     /// The purpose of this method is to spam the logs with distracting and uplifting quotes,
     /// because who doesn't find verbose logs annoying?
     ///
-    /// I am frustrated by the lack of support for [`rand::Rng::gen_range`] in an async context.
-    /// Surely there is an analogous tool I can reach for here.  Instead, I randomly generate a
-    /// `u16` value using [`rand::random`].  If the value does not exceed the number of quotes, we
-    /// can use it index into vector and pull a quote at random.  If the value exceeds the number
-    /// of quotes, we have to roll again.  Lame!
+    /// I used to be frustrated by the lack of support for [`rand::Rng::gen_range`] in an async
+    /// context, and rolled a random `u16` until it happened to land inside the vector of quotes.
+    /// Lame!  The culprit was [`rand::thread_rng`], which is not [`Send`].  Now each imp carries a
+    /// [`Picker`] with a [`Send`]-friendly random number generator, and a [`crate::Pick`] strategy
+    /// chosen by its [`Temper`].  We ask the picker for the next quote using
+    /// [`Picker::next`].
     ///
     /// When we finally have a quote, we send it to the [`ImpKing`] wrapped in a
-    /// [`Hijinks::Vandalize`] variant.  This variant includes the quote captured as a string.
-    /// We convert the quote to a string using the [`Quote::graffiti`] method.
-    /// We include the `name` of the `Imp`, so you can like them on X or something.
+    /// [`Hijinks::Vandalize`] variant.  This variant includes a [`Graffiti`] holding the quote,
+    /// along with the `name` and `id` of the `Imp`, so you can like them on X or something.  The
+    /// application turns the [`Graffiti`] into a message using a [`crate::Scrawl`] template.
     ///
     /// Not being the bravest of species, after sending the [`Hijinks`], the `Imp` goes into
     /// hiding using [`Imp::pause`].
+    ///
+    /// Before picking a quote, the imp checks whether the [`ImpKing`] has sent around a fresh
+    /// corpus, using [`Imp::study`].
     #[tracing::instrument(skip_all)]
-    pub async fn vandalize(&self) -> Arrive<()> {
-        let mut idx = 0;
-        let mut set = false;
-        while !set {
-            let trial: u16 = rand::random();
-            let trial = trial as usize;
-            if trial < self.quotes.len() {
-                idx = trial;
-                set = true;
-            }
+    pub async fn vandalize(&mut self) -> Arrive<()> {
+        self.study();
+        if let Some(quote) = self.picker.next(&self.quotes) {
+            let graffiti = Graffiti::new(self.id, self.name.clone(), quote);
+            self.tx.send(Hijinks::Vandalize(graffiti)).await?;
+        } else {
+            tracing::warn!("{} has nothing to say.", self.name);
         }
-        self.tx
-            .send(Hijinks::Vandalize(format!(
-                "{} says: {}",
-                self.name, self.quotes[idx]
-            )))
-            .await?;
         Self::pause().await;
         Ok(())
     }
 
+    /// The `study` method checks the `corpus` receiver for new quotes from the [`ImpKing`].  The
+    /// purpose of this method is to let the imps learn new material without being dismissed and
+    /// summoned all over again.
+    ///
+    /// The [`watch::Receiver::has_changed`] method tells us whether the king has sent a new corpus
+    /// since we last looked.  If so, we sift the new corpus through our [`Temper`] using
+    /// [`Temper::quotes`].  When the sifted quotes differ from the ones we had, we reset the
+    /// [`Picker`], since the old indices no longer mean anything.  If the king has left the
+    /// building, the receiver returns an error, and we keep the quotes we have.
+    ///
+    /// New ratings arrive on the `ratings` receiver instead, and we stamp them onto our quotes in
+    /// place with [`Ledger::stamp`].  The quotes stay where they were, so the picker keeps its
+    /// place, and a vote does not cost the shuffle bag its promise of no repeats.
+    pub fn study(&mut self) {
+        if self.corpus.has_changed().unwrap_or_default() {
+            let corpus = self.corpus.borrow_and_update().clone();
+            let quotes = self.temper.quotes(&corpus);
+            if !self.quotes.same_as(&quotes) {
+                self.picker.reset();
+                tracing::trace!("{} learned {} new quotes.", self.name, quotes.len());
+            }
+            self.quotes = quotes;
+        }
+        if self.ratings.has_changed().unwrap_or_default() {
+            self.ratings.borrow_and_update().stamp(&mut self.quotes);
+            tracing::trace!("{} heard new ratings.", self.name);
+        }
+    }
+
     /// The `hijinks` method randomizes `Imp` actions between meddling and vandalization.  The
     /// purpose of this method is to inject some variety into the types of [`Hijinks`] and keep the
     /// user on their toes.
@@ -245,7 +317,7 @@ impl Imp {
 /// parent-level message passing mechanism from async -> sync.  In `main.rs`, we create an
 /// event loop using [`Hijinks`] as our custom event type:
 ///
-/// ```
+/// ```ignore
 /// let event_loop = event_loop::EventLoop::<Hijinks>::with_user_event().build()?;
 /// ```
 ///
@@ -267,13 +339,17 @@ pub enum Hijinks {
     /// that contains the action information for interpretation by the application.
     Meddle(Meddle),
     /// The `Vandalize` variant signals that the [`Imp`] wants to log an inspirational quote at the
-    /// `INFO` level.  We format the quote as a [`String`] contained in the variant, to streamline
-    /// logging the message on the application side.
-    Vandalize(String),
+    /// `INFO` level.  We used to format the quote as a [`String`] on the imp side, but the
+    /// application knows things the imp does not, like how many windows are open, so now we send
+    /// the ingredients in a [`Graffiti`] and let the application format the message.
+    Vandalize(Graffiti),
     /// The `Filch` variant signals that the [`Imp`] is out of [`Frame`] instances, and is
     /// requesting more.  The [`Filch`] struct contained in the variant holds a transmitter that
     /// the application uses to send back more frames.
     Filch(Filch),
+    /// The `Cue` variant signals that a [`crate::Routine`] playing in the background wants the
+    /// application to perform an [`Act`] on the window with the [`window::WindowId`].
+    Cue(window::WindowId, Act),
 }
 
 /// The `Meddle` struct contains the information necessary for the application to perform the
@@ -289,6 +365,23 @@ pub struct Meddle {
     title: String,
 }
 
+/// The `Graffiti` struct contains the makings of a vandalized log message.  The purpose of this
+/// struct is to carry a [`Quote`] from an [`Imp`] to the application, along with who picked it
+/// and when, so the application can fill in a [`crate::Scrawl`] template.
+///
+/// * The `id` field holds the number of the imp.
+/// * The `imp` field holds the name of the imp.
+/// * The `quote` field holds the quote.
+/// * The `time` field holds the local time the imp picked the quote, set by [`Graffiti::new`].
+#[derive(Debug, Clone, PartialEq, Eq, derive_new::new, derive_getters::Getters)]
+pub struct Graffiti {
+    id: usize,
+    imp: String,
+    quote: Quote,
+    #[new(value = "chrono::Local::now()")]
+    time: chrono::DateTime<chrono::Local>,
+}
+
 /// The `Filch` struct contains a [`oneshot::Sender`] so the application can send a response to the
 /// requestor.  The purpose of this struct is to both signal to the application that the [`Imp`]
 /// process is out of [`Frame`] instances, and to provide the application with a means of providing
@@ -319,19 +412,28 @@ pub struct Filch {
 ///
 /// ## Fields ##
 ///
+/// * **corpus** - Inspirational quotes to pass along to [`Imp`] types.  Imps are not allowed to
+///   pass along quotes the `ImpKing` has not already heard.  The quotes live in a
+///   [`watch::Sender`], so that when the `ImpKing` hears new quotes, every imp hears them too.
+/// * **ratings** - The [`Ledger`] of quote ratings, in a [`watch::Sender`] of its own, so a new
+///   rating reaches the imps without sending them a whole new corpus.  The application keeps a
+///   clone of the sender, and sends the ledger after every vote, see [`crate::App::rate`].
 /// * **frames** - A vector of [`Frame`] instances to pass to [`Imp`] types.
+/// * **lore** - Describes where the quotes came from, so the `ImpKing` can check for new ones.
 /// * **proxy** - The event loop proxy used to send messages back to the event loop.
-/// * **quotes** - Inspirational quotes to pass along to [`Imp`] types.  Imps are not allowed to
-///   pass along quotes the `ImpKing` has not already heard.
 /// * **rx** - Receiver for [`Hijinks`] from [`Imp`] instances.
+/// * **tempers** - Behavior profiles to hand out to [`Imp`] types, see [`Temper`].
 /// * **tx** - Transmitter handle passed to an [`Imp`] to perform [`Hijinks`].
 
 #[derive(Debug)]
 pub struct ImpKing {
+    corpus: watch::Sender<Quotes>,
     frames: Vec<Frame>,
+    lore: Lore,
     proxy: event_loop::EventLoopProxy<Hijinks>,
-    quotes: Quotes,
+    ratings: watch::Sender<Ledger>,
     rx: mpsc::Receiver<Hijinks>,
+    tempers: Tempers,
     tx: mpsc::Sender<Hijinks>,
 }
 
@@ -342,10 +444,13 @@ impl ImpKing {
     /// `ImpKing` will use to relay messages back to the application.  The `frames` parameter
     /// provides the reservoir of [`Frame`] instances that the `ImpKing` will give out to the [`Imp`] types.
     /// The `buffer` argument determines the capacity of the [`mpsc::channel`] used to pass
-    /// [`Hijinks`] from the [`Imp`] types back to the `ImpKing`.
+    /// [`Hijinks`] from the [`Imp`] types back to the `ImpKing`.  The `lore` argument describes
+    /// where to find the quotes, and the `tempers` argument holds the behavior profiles the
+    /// `ImpKing` will hand out to his minions.  The `ratings` argument is the channel the
+    /// application uses to send the [`Ledger`] to the imps whenever the user votes.
     ///
-    /// First we attempt to read [`Quotes`] from the `data` directory, where there just so happens to
-    /// be a file called `quotes.csv`.  We deserialize the contents using [`Quotes::from_path`].
+    /// First we read [`Quotes`] using [`Lore::quotes`], which falls back on the quotes compiled
+    /// into the binary when the user has not configured any quote files of their own.
     /// We then create the [`mpsc::channel`] passing in `buffer` as the argument, so we can pass
     /// these into the new instance of `ImpKing`.
     #[tracing::instrument(skip_all)]
@@ -353,17 +458,22 @@ impl ImpKing {
         proxy: event_loop::EventLoopProxy<Hijinks>,
         buffer: usize,
         frames: Vec<Frame>,
+        lore: &Lore,
+        tempers: &Tempers,
+        ratings: watch::Sender<Ledger>,
     ) -> Arrive<Self> {
-        let path = "data/quotes.csv";
-        // let path = "/home/erik/code/tardy/data/quotes.csv";
-        let quotes = Quotes::from_path(path.into())?;
+        let quotes = lore.quotes();
         let (tx, rx) = mpsc::channel(buffer);
         tracing::trace!("Imp King has {} quotes.", quotes.len());
+        let (corpus, _) = watch::channel(quotes);
         let imp_king = Self {
+            corpus,
             frames,
+            lore: lore.clone(),
             proxy,
-            quotes,
+            ratings,
             rx,
+            tempers: tempers.clone(),
             tx,
         };
         Ok(imp_king)
@@ -373,6 +483,11 @@ impl ImpKing {
     /// The purpose of this struct is to enable the `ImpKing` to create minions, so that the
     /// minions can do the hard work of making [`Hijinks`], while he sits back and relaxes.
     /// The method takes a `count` argument specifying the number of [`Imp`] instances to create.
+    ///
+    /// Each imp receives a [`Temper`] from the `tempers` field in turn, using [`Tempers::nth`].
+    /// The temper may rename the imp, filters the quotes the imp receives using
+    /// [`Temper::quotes`], and chooses the [`crate::Pick`] strategy of the imp.  Imps
+    /// without a temper receive the full corpus and pick quotes at random.
     #[tracing::instrument(skip_all)]
     pub fn imps(&self, count: usize) -> Vec<Imp> {
         let gen = names::Generator::default();
@@ -382,7 +497,7 @@ impl ImpKing {
             .collect::<Vec<String>>();
         let mut imps = Vec::new();
         let mut frame_drain = self.frames.clone();
-        for name in names.into_iter() {
+        for (idx, name) in names.into_iter().enumerate() {
             let mut frames = Vec::new();
             while frames.len() < FRAMES {
                 if let Some(value) = frame_drain.pop() {
@@ -392,7 +507,10 @@ impl ImpKing {
                     return Vec::new();
                 }
             }
-            let imp = Imp::new(frames, name, self.quotes.clone(), self.tx.clone());
+            let temper = self.tempers.nth(idx).cloned().unwrap_or_default();
+            let corpus = self.corpus.subscribe();
+            let ratings = self.ratings.subscribe();
+            let imp = Imp::new(corpus, ratings, frames, idx, name, temper, self.tx.clone());
             imps.push(imp)
         }
         imps
@@ -445,6 +563,47 @@ impl ImpKing {
         Ok(())
     }
 
+    /// The `watch` method keeps an eye on the quote files described by `lore`, and sends a fresh
+    /// corpus through the `corpus` channel when the files change.  The purpose of this method is
+    /// to let the user edit their quotes while the imps are running, without a restart.
+    ///
+    /// I briefly considered a file system notification crate, but the imps are in no hurry, so we
+    /// poll instead.  Every [`crate::WATCH_INTERVAL`], we compare the [`Lore::stamps`] of the quote
+    /// files to the last stamps we saw.  When they differ, we read the files using
+    /// [`Lore::reload`].  If the new files are no good, we warn the user and keep the old corpus,
+    /// so a half-saved file does not leave the imps speechless.  Otherwise we replace the corpus
+    /// using [`watch::Sender::send_replace`], and the imps pick up the change the next time they
+    /// call [`Imp::study`].
+    ///
+    /// Returns immediately when the user has turned off watching, and returns once every imp has
+    /// dropped its receiver.  The [`Ledger`] of ratings is not our concern here, since the
+    /// application sends it to the imps itself after every vote, watching or not.
+    #[tracing::instrument(skip_all)]
+    pub async fn watch(lore: Lore, corpus: watch::Sender<Quotes>) -> Arrive<()> {
+        if !lore.watch() {
+            tracing::trace!("Not watching for new quotes.");
+            return Ok(());
+        }
+        let mut stamps = lore.stamps();
+        let mut interval = time::interval(crate::WATCH_INTERVAL);
+        while !corpus.is_closed() {
+            interval.tick().await;
+            let fresh = lore.stamps();
+            if fresh == stamps {
+                continue;
+            }
+            stamps = fresh;
+            match lore.reload() {
+                Ok(quotes) => {
+                    tracing::info!("Imp King heard {} new quotes.", quotes.len());
+                    corpus.send_replace(quotes);
+                }
+                Err(e) => tracing::warn!("Keeping the old quotes, could not reload: {e}"),
+            }
+        }
+        Ok(())
+    }
+
     /// One element that has hung me up so far is Ch. 17 listing 17-11.  In this example there is
     /// little consequence from dropping my async tasks in a sloppy manner, but I am unable
     /// to use the equivalent of `trpl::join_all`. I have tried refactoring the `spawn_imps`
@@ -455,15 +614,22 @@ impl ImpKing {
     /// Having written it, I am not confident that this method is correct, because I am not sure
     /// how to observe processes failing to get cleaned up.  When my kids leave the Legos out on
     /// the floor, it's more obvious.
+    ///
+    /// The king now has a third job, keeping an eye on the quote files with [`ImpKing::watch`],
+    /// which joins the other two.
     #[tracing::instrument(skip_all)]
     pub async fn reign(&mut self, count: usize) -> Arrive<()> {
         let imps = self.imps(count);
         let handles = ImpKing::spawn_imps(imps);
+        let watcher = ImpKing::watch(self.lore.clone(), self.corpus.clone());
         let listener = self.listen();
-        let (imp_res, king_res) = tokio::join!(handles, listener);
+        let (imp_res, watch_res, king_res) = tokio::join!(handles, watcher, listener);
         if let Err(blame) = imp_res {
             tracing::warn!("Problem with imps: {blame}");
         }
+        if let Err(blame) = watch_res {
+            tracing::warn!("Problem watching quotes: {blame}");
+        }
         if let Err(blame) = king_res {
             tracing::warn!("Probelm with Imp King: {blame}");
         }
@@ -478,6 +644,21 @@ impl ImpKing {
 ///   `author` is `None`.
 /// * The `quote` field contains the inspirational saying.
 ///
+/// The csv headers are in `PascalCase`, but the other formats read by [`Tome`] tend toward
+/// lower case keys, so we accept either using `#[serde(alias)]`.
+///
+/// Quotes may optionally carry some metadata, for use in sorting the wheat from the chaff:
+///
+/// * The `tags` field holds a list of free-form labels.  In a csv file, separate tags in the
+///   `Tags` column with semicolons, e.g. `"humor;baseball"`.  Formats with proper lists may use
+///   either a list or a semicolon-separated string.
+/// * The `category` field holds a single broader grouping, like `Sports`.
+/// * The `language` field holds the language of the quote, like `en`.
+/// * The `rating` field holds a score for the quote, used by [`crate::Weight::Rating`].  Higher
+///   is better, and a missing rating counts as zero.
+///
+/// All four are optional, so the original two-column csv still reads just fine.
+///
 /// I struggled to get the [`derive_more::Display`] trait working the way I wanted.  Turns out I
 /// can simply pass the custom function back to the fancy macro.  Honestly its not much better than
 /// manually writing out the impl for `Display`... but it feels lazier so I am doing it.
@@ -490,6 +671,8 @@ impl ImpKing {
     PartialOrd,
     Ord,
     Hash,
+    derive_new::new,
+    derive_getters::Getters,
     derive_more::Display,
     serde::Serialize,
     serde::Deserialize,
@@ -497,8 +680,99 @@ impl ImpKing {
 #[serde(rename_all = "PascalCase")]
 #[display("{}", self.graffiti())]
 pub struct Quote {
+    #[serde(alias = "author")]
     author: Option<String>,
+    #[serde(alias = "quote")]
     quote: String,
+    #[new(default)]
+    #[serde(
+        alias = "tags",
+        default,
+        deserialize_with = "read_tags",
+        serialize_with = "write_tags"
+    )]
+    tags: Vec<String>,
+    #[new(default)]
+    #[serde(alias = "category", default)]
+    category: Option<String>,
+    #[new(default)]
+    #[serde(alias = "language", default)]
+    language: Option<String>,
+    #[new(default)]
+    #[serde(alias = "rating", default)]
+    rating: Option<i32>,
+}
+
+/// The `read_tags` function deserializes the `tags` field of [`Quote`] from either a
+/// semicolon-separated string or a list of strings.  Csv files can only offer the former, while
+/// JSON, TOML and YAML can offer either.  Empty tags are discarded.
+fn read_tags<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    struct Tags;
+
+    impl<'de> serde::de::Visitor<'de> for Tags {
+        type Value = Vec<String>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a semicolon-separated string or a list of strings")
+        }
+
+        fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+            Ok(value
+                .split(';')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .map(String::from)
+                .collect())
+        }
+
+        fn visit_u64<E: serde::de::Error>(self, value: u64) -> Result<Self::Value, E> {
+            self.visit_str(&value.to_string())
+        }
+
+        fn visit_i64<E: serde::de::Error>(self, value: i64) -> Result<Self::Value, E> {
+            self.visit_str(&value.to_string())
+        }
+
+        fn visit_f64<E: serde::de::Error>(self, value: f64) -> Result<Self::Value, E> {
+            self.visit_str(&value.to_string())
+        }
+
+        fn visit_unit<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+            Ok(Vec::new())
+        }
+
+        fn visit_none<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+            Ok(Vec::new())
+        }
+
+        fn visit_some<D: serde::Deserializer<'de>>(
+            self,
+            deserializer: D,
+        ) -> Result<Self::Value, D::Error> {
+            deserializer.deserialize_any(self)
+        }
+
+        fn visit_seq<A: serde::de::SeqAccess<'de>>(
+            self,
+            mut seq: A,
+        ) -> Result<Self::Value, A::Error> {
+            let mut tags = Vec::new();
+            while let Some(tag) = seq.next_element::<String>()? {
+                if !tag.trim().is_empty() {
+                    tags.push(tag.trim().to_string());
+                }
+            }
+            Ok(tags)
+        }
+    }
+
+    deserializer.deserialize_any(Tags)
+}
+
+/// The `write_tags` function serializes the `tags` field of [`Quote`] as a semicolon-separated
+/// string, so that a [`Quote`] can round trip through a csv file.
+fn write_tags<S: serde::Serializer>(tags: &[String], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&tags.join(";"))
 }
 
 impl Quote {
@@ -513,6 +787,59 @@ impl Quote {
             format!("'{}' - Unknown", self.quote)
         }
     }
+
+    /// The `tidy` method returns a copy of the quote with the whitespace and quotation marks
+    /// cleaned up.  Runs of whitespace collapse to a single space, leading and trailing
+    /// whitespace goes away, and curly quotation marks become straight ones.  An author that
+    /// tidies down to nothing becomes [`None`].  Used by [`crate::Audit`].
+    pub fn tidy(&self) -> Self {
+        let tidy = |s: &str| {
+            s.replace(['“', '”', '„'], "\"")
+                .replace(['‘', '’'], "'")
+                .split_whitespace()
+                .collect::<Vec<&str>>()
+                .join(" ")
+        };
+        let mut quote = self.clone();
+        quote.author = self
+            .author
+            .as_deref()
+            .map(tidy)
+            .filter(|author| !author.is_empty());
+        quote.quote = tidy(&self.quote);
+        quote
+    }
+
+    /// The `key` method returns a stable identifier for the quote, as sixteen hex digits.  The
+    /// purpose of this method is to recognize the same quote across files and sessions, for
+    /// keeping score in a [`crate::Ledger`] and keeping history in a journal.
+    ///
+    /// We hash the author and quote after [`Quote::tidy`], so that stray whitespace or curly
+    /// quotation marks do not change the key, and leave the metadata out of it, so that retagging
+    /// a quote does not either.  The hash is 64-bit FNV-1a, chosen because it is tiny and will
+    /// never change out from under us, unlike [`std::collections::hash_map::DefaultHasher`],
+    /// which makes no promises about stability between Rust releases.
+    pub fn key(&self) -> String {
+        let tidy = self.tidy();
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let author = tidy.author.as_deref().unwrap_or_default();
+        for byte in author.bytes().chain([0x1f]).chain(tidy.quote.bytes()) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        format!("{hash:016x}")
+    }
+
+    /// The `set_rating` method replaces the rating of the quote.  Used by [`crate::Ledger::stamp`].
+    pub fn set_rating(&mut self, rating: Option<i32>) {
+        self.rating = rating;
+    }
+
+    /// The `has_tag` method returns `true` if `tag` matches one of the tags on the quote,
+    /// ignoring case.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
 }
 
 /// The `Quotes` struct is a wrapper around a vector of type [`Quote`].
@@ -532,6 +859,7 @@ impl Quote {
     PartialOrd,
     Ord,
     Hash,
+    derive_new::new,
     derive_more::Deref,
     derive_more::DerefMut,
     serde::Serialize,
@@ -540,20 +868,124 @@ impl Quote {
 pub struct Quotes(Vec<Quote>);
 
 impl Quotes {
-    /// The `from_path` method attempts to read quotes from a csv file located at `path`.
+    /// The `from_path` method attempts to read quotes from the file located at `path`.
     /// This is the means by which we bring quotes into the application for use by the [`ImpKing`].
-    /// We open the file location using [`fs::File::open`], then attempt to read the csv data using
-    /// [`csv::Reader::from_reader`].  For each row of data, we attempt to serialize the contents
-    /// into a [`Quote`] struct, and push it into a vector holding the results, which we pass to
-    /// user wrapped in a `Quotes` struct.
-    ///
-    /// Will [`crate::Blame::Io`] if the file path is bad, and will [`crate::Blame::Csv`] if the contents fail to
-    /// deserialize into the [`Quote`] type. On second thought, there is no need bubble up an error
-    /// if the csv is bad, we will just hand the user a warning.
+    /// We read the file to a string using [`fs::read_to_string`], determine the format of the
+    /// file using [`Tome::from_path`], and hand the contents to [`Tome::read`] for parsing.
+    /// If `path` points to a directory, we read every file inside using [`Quotes::from_dir`].
+    ///
+    /// Will [`crate::Blame::Io`] if the file path is bad.  Csv, JSON Lines and text files warn
+    /// about bad rows rather than bubbling up an error, while a malformed JSON, TOML or YAML
+    /// document will fail outright, as described in [`Tome::read`].
     pub fn from_path(path: path::PathBuf) -> Arrive<Self> {
-        let file = fs::File::open(path)?;
+        if path.is_dir() {
+            return Self::from_dir(path);
+        }
+        let contents = fs::read_to_string(&path)?;
+        let tome = Tome::from_path(&path, &contents);
+        tracing::trace!("Reading {} as {tome}.", path.display());
+        tome.read(&contents)
+    }
+
+    /// The `from_paths` method reads and merges the quotes from each of the `paths` using
+    /// [`Quotes::from_path`] and [`Quotes::merge`].  The purpose of this method is to combine
+    /// several collections of quotes into one.  Fails on the first path that fails.
+    pub fn from_paths(paths: &[path::PathBuf]) -> Arrive<Self> {
+        let mut quotes = Self::default();
+        for path in paths {
+            quotes.merge(Self::from_path(path.clone())?);
+        }
+        Ok(quotes)
+    }
+
+    /// The `from_path_strict` method reads quotes from `path` like [`Quotes::from_path`], except
+    /// that a bad row fails the whole file, and a bad file fails the whole directory.  The
+    /// purpose of this method is to tell a good file from a half-saved one, see
+    /// [`Lore::reload`].
+    ///
+    /// Will [`crate::Blame::Io`] or a parsing error if a file fails to read, as described in
+    /// [`Tome::rows`], and will [`Excuse::BadRow`] if a single quote fails to read.
+    pub fn from_path_strict(path: &path::Path) -> Arrive<Self> {
+        if path.is_dir() {
+            let mut quotes = Self::default();
+            for file in Self::files(path)? {
+                quotes.merge(Self::from_path_strict(&file)?);
+            }
+            return Ok(quotes);
+        }
+        let contents = fs::read_to_string(path)?;
+        let tome = Tome::from_path(path, &contents);
+        let mut quotes = Vec::new();
+        for row in tome.rows(&contents)? {
+            match row.entry() {
+                Ok(quote) => quotes.push(quote.clone()),
+                Err(problem) => {
+                    return Err(Blame::Excuse(Excuse::BadRow {
+                        path: path.display().to_string(),
+                        row: *row.row(),
+                        problem: problem.clone(),
+                    }))
+                }
+            }
+        }
+        Ok(Self(quotes))
+    }
+
+    /// The `from_paths_strict` method reads and merges the quotes from each of the `paths` using
+    /// [`Quotes::from_path_strict`].  Fails on the first path that fails.
+    pub fn from_paths_strict(paths: &[path::PathBuf]) -> Arrive<Self> {
+        let mut quotes = Self::default();
+        for path in paths {
+            quotes.merge(Self::from_path_strict(path)?);
+        }
+        Ok(quotes)
+    }
+
+    /// The `files` method lists the files in the directory at `path` with an extension
+    /// recognized by [`Tome::from_extension`], in lexical order, so the resulting order of quotes
+    /// does not depend on the whims of the filesystem.
+    ///
+    /// Will [`crate::Blame::Io`] if the directory cannot be read.
+    pub fn files(path: &path::Path) -> Arrive<Vec<path::PathBuf>> {
+        let mut paths = fs::read_dir(path)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| {
+                p.is_file()
+                    && p.extension()
+                        .and_then(|ext| ext.to_str())
+                        .and_then(Tome::from_extension)
+                        .is_some()
+            })
+            .collect::<Vec<path::PathBuf>>();
+        paths.sort();
+        Ok(paths)
+    }
+
+    /// The `from_dir` method reads every file in the directory at `path` listed by
+    /// [`Quotes::files`], merging the results into a single collection.  Files that fail to read
+    /// produce a warning and are skipped.
+    ///
+    /// Will [`crate::Blame::Io`] if the directory cannot be read.
+    pub fn from_dir(path: path::PathBuf) -> Arrive<Self> {
+        let mut quotes = Self::default();
+        for path in Self::files(&path)? {
+            match Self::from_path(path.clone()) {
+                Ok(found) => quotes.merge(found),
+                Err(e) => tracing::warn!("Could not read quotes from {}: {e}", path.display()),
+            }
+        }
+        Ok(quotes)
+    }
+
+    /// The `from_reader` method reads quotes in csv format from any source implementing
+    /// [`io::Read`].  The purpose of this method is to share the parsing logic between quotes
+    /// that live on the filesystem and quotes that live inside the binary.
+    ///
+    /// Like [`Quotes::from_path`], rows that fail to deserialize into a [`Quote`] produce a warning
+    /// instead of an error, so a single bad row does not cost us the rest of the file.
+    pub fn from_reader<R: io::Read>(reader: R) -> Self {
         let mut quotes = Vec::new();
-        let mut rdr = csv::Reader::from_reader(file);
+        let mut rdr = csv::Reader::from_reader(reader);
         for result in rdr.deserialize() {
             match result {
                 Ok(quote) => quotes.push(quote),
@@ -562,6 +994,185 @@ impl Quotes {
                 }
             }
         }
-        Ok(Self(quotes))
+        Self(quotes)
+    }
+
+    /// The `with_tag` method returns the quotes tagged with `tag`, ignoring case.
+    pub fn with_tag(&self, tag: &str) -> Self {
+        self.select(|quote| quote.has_tag(tag))
+    }
+
+    /// The `by_author` method returns the quotes attributed to `author`, ignoring case.  Pass
+    /// `Unknown` to get the quotes without an attribution, matching the convention of
+    /// [`Quote::graffiti`].
+    pub fn by_author(&self, author: &str) -> Self {
+        self.select(|quote| match quote.author() {
+            Some(name) => name.eq_ignore_ascii_case(author),
+            None => author.eq_ignore_ascii_case("unknown"),
+        })
+    }
+
+    /// The `with_keyword` method returns the quotes containing `keyword` in the text of the
+    /// quote, ignoring case.
+    pub fn with_keyword(&self, keyword: &str) -> Self {
+        let keyword = keyword.to_lowercase();
+        self.select(|quote| quote.quote().to_lowercase().contains(&keyword))
+    }
+
+    /// The `with_length` method returns the quotes with a character count inside `range`.
+    /// The purpose of this method is to keep the long-winded quotes out of the logs, or to
+    /// insist upon them.
+    pub fn with_length<R: std::ops::RangeBounds<usize>>(&self, range: R) -> Self {
+        self.select(|quote| range.contains(&quote.quote().chars().count()))
+    }
+
+    /// The `in_category` method returns the quotes in `category`, ignoring case.
+    pub fn in_category(&self, category: &str) -> Self {
+        self.select(|quote| {
+            quote
+                .category()
+                .as_ref()
+                .is_some_and(|c| c.eq_ignore_ascii_case(category))
+        })
+    }
+
+    /// The `in_language` method returns the quotes in `language`, ignoring case.
+    pub fn in_language(&self, language: &str) -> Self {
+        self.select(|quote| {
+            quote
+                .language()
+                .as_ref()
+                .is_some_and(|l| l.eq_ignore_ascii_case(language))
+        })
+    }
+
+    /// The `select` method returns a new `Quotes` holding clones of the quotes for which
+    /// `predicate` returns `true`.  The query methods above are all thin wrappers around this one.
+    pub fn select<F: Fn(&Quote) -> bool>(&self, predicate: F) -> Self {
+        Self(self.iter().filter(|q| predicate(q)).cloned().collect())
+    }
+
+    /// The `builtin` method reads the quotes compiled into the binary from [`BUILTIN_QUOTES`].
+    /// The purpose of this method is to make sure the imps always have something to say, even
+    /// when the program runs from a directory without a `data` folder, or the user has not
+    /// configured a quotes file of their own.
+    ///
+    /// Since the corpus is baked in at compile time, there is no [`crate::Blame::Io`] to worry
+    /// about, and we return `Self` directly.
+    pub fn builtin() -> Self {
+        Self::from_reader(BUILTIN_QUOTES.as_bytes())
+    }
+
+    /// The `same_as` method returns `true` if `other` holds the same quotes in the same order,
+    /// going by [`Quote::key`], so that quotes differing only in their ratings or other metadata
+    /// count as the same.
+    pub fn same_as(&self, other: &Quotes) -> bool {
+        self.iter().map(Quote::key).eq(other.iter().map(Quote::key))
+    }
+
+    /// The `merge` method appends the quotes in `other` to `self`, skipping any quote already
+    /// present.  The purpose of this method is to combine the built-in corpus with quotes supplied
+    /// by the user, without repeating the quotes they share.
+    pub fn merge(&mut self, other: Quotes) {
+        let mut seen = self.iter().cloned().collect::<HashSet<Quote>>();
+        for quote in other.0 {
+            if seen.insert(quote.clone()) {
+                self.push(quote);
+            }
+        }
+    }
+}
+
+/// The `BUILTIN_QUOTES` constant holds the contents of `data/quotes.csv`, embedded into the binary
+/// at compile time using [`include_str`].  Read it into a [`Quotes`] struct using
+/// [`Quotes::builtin`].
+pub const BUILTIN_QUOTES: &str = include_str!("../data/quotes.csv");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch(name: &str) -> path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("tardy-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn imp(quotes: Quotes) -> (Imp, watch::Sender<Quotes>, watch::Sender<Ledger>) {
+        let (corpus, corpus_rx) = watch::channel(quotes);
+        let (ratings, ratings_rx) = watch::channel(Ledger::default());
+        let (tx, _) = mpsc::channel(1);
+        let temper = Temper::default().with_pick(crate::Pick::RoundRobin);
+        let imp = Imp::new(
+            corpus_rx,
+            ratings_rx,
+            Vec::new(),
+            0,
+            "Imp".into(),
+            temper,
+            tx,
+        );
+        (imp, corpus, ratings)
+    }
+
+    fn numbered(count: usize) -> Quotes {
+        Quotes::new(
+            (0..count)
+                .map(|idx| Quote::new(None, idx.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn new_ratings_keep_picker_place() {
+        let quotes = numbered(3);
+        let (mut imp, corpus, ratings) = imp(quotes.clone());
+        assert_eq!(imp.picker.next(&imp.quotes).unwrap().quote(), "0");
+        let mut ledger = Ledger::default();
+        ledger.rate(&quotes[2], 3);
+        ratings.send_replace(ledger);
+        corpus.send_replace(quotes.clone());
+        imp.study();
+        assert_eq!(imp.quotes[2].rating(), &Some(3));
+        assert_eq!(imp.picker.next(&imp.quotes).unwrap().quote(), "1");
+    }
+
+    #[test]
+    fn new_quotes_reset_picker() {
+        let (mut imp, corpus, _ratings) = imp(numbered(3));
+        assert_eq!(imp.picker.next(&imp.quotes).unwrap().quote(), "0");
+        let mut quotes = numbered(3);
+        quotes.reverse();
+        corpus.send_replace(quotes);
+        imp.study();
+        assert_eq!(imp.picker.next(&imp.quotes).unwrap().quote(), "2");
+    }
+
+    #[test]
+    fn strict_read_fails_on_bad_row() {
+        let dir = scratch("strict-row");
+        let file = dir.join("quotes.jsonl");
+        fs::write(
+            &file,
+            "{\"author\": \"Ada\", \"quote\": \"Hello.\"}\n{\"author\": \"Ada\"\n",
+        )
+        .unwrap();
+        assert_eq!(Quotes::from_path(file.clone()).unwrap().len(), 1);
+        match Quotes::from_path_strict(&file) {
+            Err(Blame::Excuse(Excuse::BadRow { row, .. })) => assert_eq!(row, 2),
+            other => panic!("expected a bad row, got {other:?}"),
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn strict_read_fails_on_bad_file_in_dir() {
+        let dir = scratch("strict-dir");
+        fs::write(dir.join("a.txt"), "Hello. — Ada\n").unwrap();
+        fs::write(dir.join("b.json"), "[{\"author\": ").unwrap();
+        assert_eq!(Quotes::from_path(dir.clone()).unwrap().len(), 1);
+        assert!(Quotes::from_path_strict(&dir).is_err());
+        fs::remove_dir_all(dir).unwrap();
     }
 }
@@ -0,0 +1,147 @@
+use crate::{Arrive, Graffiti};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::{fs, io, path};
+
+/// The `journal` module holds the [`Journal`] struct, a persistent record of every quote the imps
+/// have vandalized, and the [`Entry`] struct, a single line of that record.
+///
+/// # Keeping a Journal
+///
+/// Once an imp scrawls a quote into the logs, the quote scrolls off the terminal and is gone for
+/// good.  That is a shame when the quote was a good one, and you cannot remember who said it.  So
+/// now the application writes down each [`Graffiti`] as an [`Entry`] in a journal file, using
+/// [`Journal::record`], right after logging the message.
+///
+/// The journal is a JSON Lines file, one entry per line.  We append to the file rather than
+/// rewriting it, so recording stays cheap no matter how long the imps have been at it, and a crash
+/// in the middle of a write costs at most one line.  A line that fails to parse is skipped with a
+/// warning when the journal loads, rather than losing the whole history.
+///
+/// The location of the journal file lives in the `journal` field of [`crate::Lore`], and the
+/// history is available from the command line with `tardy history`.
+#[derive(
+    Debug, Default, Clone, PartialEq, Eq, derive_new::new, derive_more::Deref, derive_more::DerefMut,
+)]
+pub struct Journal(Vec<Entry>);
+
+/// The `Entry` struct records a single vandalized quote in the [`Journal`].
+///
+/// * The `time` field holds the local time the imp picked the quote.
+/// * The `id` field holds the number of the imp.
+/// * The `imp` field holds the name of the imp.
+/// * The `key` field holds the identity of the quote, see [`crate::Quote::key`].
+/// * The `author` field holds the author of the quote, if known.
+/// * The `quote` field holds the text of the quote.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    derive_new::new,
+    derive_getters::Getters,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub struct Entry {
+    time: chrono::DateTime<chrono::Local>,
+    id: usize,
+    imp: String,
+    key: String,
+    author: Option<String>,
+    quote: String,
+}
+
+impl From<&Graffiti> for Entry {
+    fn from(graffiti: &Graffiti) -> Self {
+        let quote = graffiti.quote();
+        Self::new(
+            *graffiti.time(),
+            *graffiti.id(),
+            graffiti.imp().clone(),
+            quote.key(),
+            quote.author().clone(),
+            quote.quote().clone(),
+        )
+    }
+}
+
+/// The `JOURNAL_PATH` constant holds the default location of the [`Journal`], relative to the
+/// working directory, same as `Tardy.toml`.
+pub const JOURNAL_PATH: &str = "tardy_journal.jsonl";
+
+impl Journal {
+    /// The `load` method reads the `Journal` from the JSON Lines file at `path`.  A missing file
+    /// means the imps have not said anything yet, so we return an empty journal.  Blank lines are
+    /// ignored, and lines that fail to parse earn a warning.
+    ///
+    /// Will [`crate::Blame::Io`] if the file exists but cannot be read.
+    pub fn load(path: &path::Path) -> Arrive<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let reader = io::BufReader::new(fs::File::open(path)?);
+        let mut entries = Vec::new();
+        for (idx, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<Entry>(&line) {
+                Ok(entry) => entries.push(entry),
+                Err(e) => tracing::warn!("Skipping line {} of journal: {e}", idx + 1),
+            }
+        }
+        Ok(Self(entries))
+    }
+
+    /// The `record` method appends `entry` to the journal file at `path`, creating the file and
+    /// any missing parent directories as needed.
+    ///
+    /// Will [`crate::Blame::Io`] if the file cannot be written, and [`crate::Blame::Json`] if the
+    /// entry fails to serialize.
+    pub fn record(path: &path::Path, entry: &Entry) -> Arrive<()> {
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        Ok(())
+    }
+
+    /// The `recent` method returns the last `count` entries, oldest first.
+    pub fn recent(&self, count: usize) -> &[Entry] {
+        &self[self.len().saturating_sub(count)..]
+    }
+
+    /// The `top_authors` method counts the entries for each author, and returns the `count` most
+    /// quoted authors, most quoted first.  Ties go in alphabetical order, so the result does not
+    /// depend on the order of the hash map.  Unattributed quotes do not count.
+    pub fn top_authors(&self, count: usize) -> Vec<(String, usize)> {
+        let mut tally = HashMap::new();
+        self.iter()
+            .filter_map(|entry| entry.author().as_ref())
+            .for_each(|author| *tally.entry(author.clone()).or_insert(0usize) += 1);
+        let mut tally = tally.into_iter().collect::<Vec<(String, usize)>>();
+        tally.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        tally.truncate(count);
+        tally
+    }
+
+    /// The `export_csv` method writes the journal to `writer` as CSV, with a header row.
+    ///
+    /// Will [`crate::Blame::Csv`] if writing fails.
+    pub fn export_csv<W: io::Write>(&self, writer: W) -> Arrive<()> {
+        let mut wtr = csv::Writer::from_writer(writer);
+        for entry in self.iter() {
+            wtr.serialize(entry)?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+}
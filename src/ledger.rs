@@ -0,0 +1,89 @@
+use crate::{Arrive, Quote, Quotes};
+use std::collections::HashMap;
+use std::{fs, path};
+
+/// The `ledger` module holds the [`Ledger`] struct, which keeps score of the quotes the user likes
+/// and dislikes.
+///
+/// # Keeping Score
+///
+/// Not every inspirational quote inspires.  When an imp vandalizes the logs with a quote the user
+/// enjoyed, they can press the key bound to [`crate::Act::Upvote`], and when the quote was a dud,
+/// the key bound to [`crate::Act::Downvote`].  The rating applies to the most recent quote.
+///
+/// The `Ledger` is a wrapper around a [`HashMap`] from [`Quote::key`] to the rating of the quote,
+/// persisted as JSON to the file named by the `ratings` field of [`crate::Lore`].  Keying by a
+/// hash of the quote, rather than its position in a file, means the ratings survive edits to the
+/// quote files, and apply to the same quote wherever it appears.
+///
+/// When the [`crate::ImpKing`] reads quotes, he stamps each quote with its rating from the ledger
+/// using [`Ledger::stamp`].  The imps weigh their picks by rating, see [`crate::Weight::Rating`],
+/// so disliked quotes fade out over time.  After each vote, the application sends the ledger to
+/// the imps on a channel of their own, so a vote reaches the imps without a restart, and without
+/// making them start their picks over.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    PartialEq,
+    Eq,
+    derive_new::new,
+    derive_more::Deref,
+    derive_more::DerefMut,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub struct Ledger(HashMap<String, i32>);
+
+impl Ledger {
+    /// The `load` method reads a `Ledger` from the JSON file at `path`.  A missing file is not an
+    /// error, it just means the user has not rated anything yet, so we return an empty ledger.
+    ///
+    /// Will [`crate::Blame::Io`] if the file exists but cannot be read, and
+    /// [`crate::Blame::Json`] if the contents are not a valid ledger.
+    pub fn load(path: &path::Path) -> Arrive<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// The `save` method writes the `Ledger` to `path` as JSON, creating any missing parent
+    /// directories.
+    pub fn save(&self, path: &path::Path) -> Arrive<()> {
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// The `rating` method returns the rating of `quote`, or [`None`] if the user has never
+    /// rated it.
+    pub fn rating(&self, quote: &Quote) -> Option<i32> {
+        self.get(&quote.key()).copied()
+    }
+
+    /// The `rate` method adds `delta` to the rating of `quote`, returning the new rating.
+    /// Ratings are clamped to plus or minus [`crate::RATING_CAP`], so that a heavy finger on the
+    /// downvote key can be undone in a reasonable number of presses.
+    pub fn rate(&mut self, quote: &Quote, delta: i32) -> i32 {
+        let rating = self.entry(quote.key()).or_default();
+        *rating = (*rating + delta).clamp(-crate::RATING_CAP, crate::RATING_CAP);
+        *rating
+    }
+
+    /// The `stamp` method copies the rating of each quote in `quotes` from the ledger into the
+    /// `rating` field of the quote.  Quotes missing from the ledger keep any rating they came
+    /// with from the quote file.
+    pub fn stamp(&self, quotes: &mut Quotes) {
+        for quote in quotes.iter_mut() {
+            if let Some(rating) = self.rating(quote) {
+                quote.set_rating(Some(rating));
+            }
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use crate::Cmd;
 use std::sync::Arc;
 use winit::window;
 
@@ -25,19 +26,45 @@ use winit::window;
 /// Eventually I want to be able to share a window between the well-tested `egui` library and the
 /// relatively immature [galileo](https://docs.rs/galileo/latest/galileo/) library, but for now we
 /// are just stubbing this out for future use by wrapping it in an [`Arc`].
+///
+/// The `title` field holds the title the window was created with.  We decorate the title with
+/// the current mode of the keymap, and need the original to start from each time the mode
+/// changes.  Asking the window for its title does not help, since some platforms always answer
+/// with an empty string.
+///
+/// The `keys` field holds an optional [`Cmd`] overlay, a keymap for this window alone.  When
+/// present, [`crate::App`] looks up input in the overlay before the global keymap, so the overlay
+/// can claim keys for itself.  The [`crate::Imp`] windows use one, so that any key closes them.
 #[derive(Debug, derive_getters::Getters, derive_setters::Setters)]
 #[setters(prefix = "with_", into, borrow_self)]
 pub struct Lens {
+    keys: Option<Cmd>,
     refresh: bool,
+    title: String,
     window: Arc<window::Window>,
 }
 
 impl Lens {
-    /// The `new` method creates an instance of `Lens` from an [`Arc<window::Window>`].
-    pub fn new(window: Arc<window::Window>) -> Self {
+    /// The `new` method creates an instance of `Lens` from an [`Arc<window::Window>`], and the
+    /// `title` the window was created with.
+    pub fn new(window: Arc<window::Window>, title: &str) -> Self {
         Self {
+            keys: None,
             refresh: false,
+            title: title.to_string(),
             window,
         }
     }
+
+    /// The `keys_mut` method returns a mutable reference to the keymap overlay, if any, since
+    /// looking up keys moves the overlay along any pending sequence.
+    pub fn keys_mut(&mut self) -> Option<&mut Cmd> {
+        self.keys.as_mut()
+    }
+
+    /// The `show_mode` method sets the title of the window to the original title followed by
+    /// `mode` in brackets, like `Tardy [normal]`.
+    pub fn show_mode(&self, mode: &str) {
+        self.window.set_title(&format!("{} [{mode}]", self.title));
+    }
 }
@@ -21,7 +21,7 @@
 //! 2. Representing window state with `Lens` - [`Lens`]
 //! 3. Reading commands from a configuration file with `Cmd` - [`Cmd`]
 //!     * [`Cmd::act`]
-//!     * See also the doc comment above the impl for `From<&config::Config>`.
+//!     * See also the doc comment above the impl for `TryFrom<&config::Config>`.
 //! 4. Dispatching actions with `Act` - [`Act`]
 //!     * See also the doc comment above the impl for [`Act`].
 //!
@@ -100,17 +100,54 @@
 mod act;
 mod app;
 mod arrive;
+mod audit;
+mod capture;
+mod chord;
+mod cli;
 mod cmd;
+mod dispatch;
 mod imp;
+mod journal;
+mod ledger;
 mod lens;
+mod lore;
+mod pick;
+mod preset;
+mod register;
+mod remix;
+mod routine;
+mod scrawl;
+mod sheet;
+mod temper;
+mod tome;
 mod utils;
 
 // Since this is a small application, we lift all user-facing data types and functions to the parent namespace
 // for ease of access.
-pub use act::Act;
-pub use app::{App, Frame, FRAMES, FRAME_POOL, IMPS, MIN_SPAN};
+pub use act::{Act, MAX_COUNT};
+pub use app::{App, Frame, FRAMES, FRAME_POOL, IMPS, IMP_WINDOW, MIN_SPAN};
 pub use arrive::{Arrive, Blame, Excuse};
-pub use cmd::Cmd;
-pub use imp::{Filch, Hijinks, Imp, ImpKing, Meddle, Quote, Quotes};
+pub use audit::{fold, Audit, Finding, Flaw};
+pub use capture::{Capture, Step, CONFIG_PATH};
+pub use chord::{Chord, Sequence, ANY_KEY, PHYSICAL};
+pub use cli::{Cli, Command, HistoryCommand, QuotesCommand};
+pub use cmd::{
+    Cmd, BINDS_KEY, DEFAULT_KEYMAP, DEFAULT_MODE, DOUBLE_CLICK, KEYMAP_KEY, MACROS_KEY,
+    SEQUENCE_TIMEOUT, WHEEL_STEP,
+};
+pub use dispatch::{Deed, Dispatcher, Handler, Origin};
+pub use imp::{Filch, Graffiti, Hijinks, Imp, ImpKing, Meddle, Quote, Quotes, BUILTIN_QUOTES};
+pub use journal::{Entry, Journal, JOURNAL_PATH};
+pub use ledger::Ledger;
 pub use lens::Lens;
+pub use lore::{Lore, RATINGS_PATH, WATCH_INTERVAL};
+pub use pick::{Pick, Picker, Weight, RATING_BASE, RATING_CAP};
+pub use preset::{Preset, EMACS_KEYMAP, ONE_HANDED_KEYMAP, VIM_KEYMAP};
+pub use register::{Recorder, Registers, DEFAULT_REGISTER, REGISTERS_PATH};
+pub use remix::{Remix, Riff, REMIX_ORDER, REMIX_TRIES, REMIX_WORDS};
+pub use routine::{Cue, Routine, WAIT_KEY};
+pub use scrawl::{Scrawl, Scrawls, DEFAULT_SCRAWL, TIME_FORMAT};
+pub use sheet::Sheet;
+pub use temper::{Sift, Temper, Tempers};
+pub use tome::{Row, Tome};
 pub use utils::trace_init;
@@ -0,0 +1,205 @@
+use crate::{Arrive, Blame, Excuse, Ledger, Quotes};
+use std::time::{Duration, SystemTime};
+use std::{fs, path};
+
+/// The `lore` module holds the [`Lore`] struct, which describes where the [`crate::ImpKing`]
+/// should go looking for inspirational quotes.
+///
+/// # Where the Imps Learn Their Quotes
+///
+/// Until now, the path to `data/quotes.csv` was hard-coded into [`crate::ImpKing::summon`], so
+/// running the program from any other directory left the imps with nothing to say, and the Imp
+/// King with nothing to reign over.  Now the same file gets compiled into the binary (see
+/// [`Quotes::builtin`]), and the user can point at their own quote files in a `[quotes]` table in
+/// `Tardy.toml`:
+///
+/// ```toml
+/// [quotes]
+/// paths = ["data/my_quotes.csv"]
+/// merge = true
+/// ```
+///
+/// * The `paths` field lists quote files supplied by the user.  When empty, we use the built-in
+///   corpus.  Any format understood by [`crate::Tome`] will do, and a path to a directory reads
+///   every quote file inside.
+/// * The `merge` field indicates whether to combine the user quotes with the built-in corpus.
+///   When `false`, user quotes replace the built-in corpus entirely.
+/// * The `watch` field indicates whether the [`crate::ImpKing`] should keep an eye on the files
+///   in `paths`, and reload the quotes when they change.  Defaults to `true`.
+/// * The `ratings` field holds the path to the [`Ledger`] of quote ratings.  Defaults to
+///   [`RATINGS_PATH`].
+/// * The `journal` field holds the path to the [`crate::Journal`] of vandalized quotes.  Defaults
+///   to [`crate::JOURNAL_PATH`].
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    derive_getters::Getters,
+    derive_setters::Setters,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[setters(prefix = "with_", into)]
+#[serde(default)]
+pub struct Lore {
+    paths: Vec<path::PathBuf>,
+    merge: bool,
+    watch: bool,
+    ratings: path::PathBuf,
+    journal: path::PathBuf,
+}
+
+impl Default for Lore {
+    fn default() -> Self {
+        Self {
+            paths: Vec::new(),
+            merge: false,
+            watch: true,
+            ratings: RATINGS_PATH.into(),
+            journal: crate::JOURNAL_PATH.into(),
+        }
+    }
+}
+
+/// The `RATINGS_PATH` constant holds the default location of the [`Ledger`] of quote ratings,
+/// relative to the working directory, same as `Tardy.toml`.
+pub const RATINGS_PATH: &str = "tardy_ratings.json";
+
+/// The `WATCH_INTERVAL` constant determines how often the [`crate::ImpKing`] checks the quote
+/// files for changes.
+pub const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+impl Lore {
+    /// The `quotes` method reads [`Quotes`] from the sources described by `self`.  The purpose of
+    /// this method is to give the [`crate::ImpKing`] a corpus no matter what the user got wrong in
+    /// their configuration.
+    ///
+    /// When no `paths` are configured, we return [`Quotes::builtin`].  Otherwise we read each path
+    /// using [`Quotes::from_path`], warning about any file we cannot read.  If none of the user
+    /// files produce a quote, we fall back on the built-in corpus, and if `merge` is set, we fold
+    /// the built-in corpus in with the user quotes using [`Quotes::merge`].
+    ///
+    /// Finally, we stamp the ratings from the [`Ledger`] onto the quotes using [`Lore::rate`].
+    #[tracing::instrument(skip_all)]
+    pub fn quotes(&self) -> Quotes {
+        let mut quotes = self.unrated();
+        self.rate(&mut quotes);
+        quotes
+    }
+
+    /// The `unrated` method does the work of [`Lore::quotes`], minus the ratings.
+    fn unrated(&self) -> Quotes {
+        if self.paths.is_empty() {
+            tracing::trace!("No quote files configured, using built-in quotes.");
+            return Quotes::builtin();
+        }
+        let mut quotes = Quotes::default();
+        for path in &self.paths {
+            match Quotes::from_path(path.clone()) {
+                Ok(found) => {
+                    tracing::trace!("Read {} quotes from {}", found.len(), path.display());
+                    quotes.merge(found);
+                }
+                Err(e) => tracing::warn!("Could not read quotes from {}: {e}", path.display()),
+            }
+        }
+        if quotes.is_empty() {
+            tracing::warn!("No quotes found in configured files, using built-in quotes.");
+            Quotes::builtin()
+        } else {
+            if self.merge {
+                quotes.merge(Quotes::builtin());
+            }
+            quotes
+        }
+    }
+
+    /// The `reload` method reads [`Quotes`] from the configured `paths`, like [`Lore::quotes`],
+    /// but without the safety nets.  The purpose of this method is to find out whether a freshly
+    /// edited quote file is any good, so the [`crate::ImpKing`] can decide whether to keep the
+    /// quotes he already has.  We read with [`Quotes::from_paths_strict`], so a single bad row,
+    /// or a single bad file in a directory, fails the reload, rather than trading the corpus for
+    /// whatever rows of a half-saved file happen to parse.
+    ///
+    /// Will [`crate::Blame::Io`], a parsing error or [`Excuse::BadRow`] from
+    /// [`Quotes::from_paths_strict`] if any file fails to read, and will [`Excuse::NoQuotes`] if
+    /// the files read fine but contain no quotes.
+    /// When no paths are configured, the built-in corpus is always good.
+    pub fn reload(&self) -> Arrive<Quotes> {
+        let mut quotes = if self.paths.is_empty() {
+            Quotes::builtin()
+        } else {
+            let mut quotes = Quotes::from_paths_strict(&self.paths)?;
+            if quotes.is_empty() {
+                return Err(Blame::Excuse(Excuse::NoQuotes));
+            }
+            if self.merge {
+                quotes.merge(Quotes::builtin());
+            }
+            quotes
+        };
+        self.rate(&mut quotes);
+        Ok(quotes)
+    }
+
+    /// The `rate` method stamps `quotes` with the ratings in the [`Ledger`] at `ratings`, using
+    /// [`Ledger::stamp`].  A ledger that fails to read leaves the quotes unrated, see
+    /// [`Lore::ledger`].
+    pub fn rate(&self, quotes: &mut Quotes) {
+        self.ledger().stamp(quotes);
+    }
+
+    /// The `ledger` method reads the [`Ledger`] at `ratings`.  A ledger that fails to read earns
+    /// a warning, and we return an empty one.
+    pub fn ledger(&self) -> Ledger {
+        Ledger::load(&self.ratings).unwrap_or_else(|e| {
+            tracing::warn!("Could not read ratings: {e}");
+            Ledger::default()
+        })
+    }
+
+    /// The `stamps` method returns the modification time of each quote file in `paths`,
+    /// including the files inside any directories.  The purpose of this method is to cheaply
+    /// detect when the quotes have changed.  Comparing the stamps from one call to the next will
+    /// catch edited files, as well as files that were added or removed from a directory.
+    ///
+    /// Files that cannot be read have a stamp of [`None`], so a file that goes missing also counts
+    /// as a change.  The `ratings` file is left out, since it changes with every vote and does
+    /// not change the quotes.  Votes reach the imps from [`crate::App::rate`] instead.
+    pub fn stamps(&self) -> Vec<(path::PathBuf, Option<SystemTime>)> {
+        let mut stamps = Vec::new();
+        for path in &self.paths {
+            if path.is_dir() {
+                let files = Quotes::files(path).unwrap_or_default();
+                stamps.extend(files.into_iter().map(|p| {
+                    let time = Self::stamp(&p);
+                    (p, time)
+                }));
+            } else {
+                stamps.push((path.clone(), Self::stamp(path)));
+            }
+        }
+        stamps
+    }
+
+    /// The `stamp` method returns the modification time of the file at `path`, if any.
+    fn stamp(path: &path::Path) -> Option<SystemTime> {
+        fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+}
+
+/// The `Lore` table lives under the `quotes` key of `Tardy.toml`.  If the key is missing or
+/// malformed, we warn the user and hand back the default, which reads the built-in corpus.
+impl From<&config::Config> for Lore {
+    fn from(config: &config::Config) -> Self {
+        match config.get::<Self>("quotes") {
+            Ok(lore) => lore,
+            Err(config::ConfigError::NotFound(_)) => Self::default(),
+            Err(e) => {
+                tracing::warn!("Could not read quotes settings: {e}");
+                Self::default()
+            }
+        }
+    }
+}
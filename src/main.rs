@@ -1,9 +1,14 @@
-use tardy::{trace_init, App, Arrive, Hijinks};
+use clap::Parser;
+use tardy::{trace_init, App, Arrive, Cli, Hijinks};
 use winit::event_loop;
 
 #[tokio::main]
 async fn main() -> Arrive<()> {
     trace_init();
+    let cli = Cli::parse();
+    if let Some(command) = cli.command {
+        return command.run();
+    }
     let event_loop = event_loop::EventLoop::<Hijinks>::with_user_event().build()?;
     let proxy = event_loop.create_proxy();
     event_loop.set_control_flow(event_loop::ControlFlow::Wait);
@@ -0,0 +1,386 @@
+use crate::{Quote, Quotes, Remix, Riff};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+
+/// The `pick` module holds the [`Pick`] enum, which names a strategy for choosing the next quote,
+/// and the [`Picker`] struct, which carries out the strategy.
+///
+/// # Picking Quotes
+///
+/// The original [`crate::Imp::vandalize`] rolled a random `u16` until the value landed inside the
+/// vector of quotes.  Quotes repeated frequently, a small corpus could take a long time to land a
+/// roll, and a large corpus could never reach past index 65,535.  The trouble was that
+/// [`rand::thread_rng`] is not [`Send`], so it cannot live across an `.await` inside a spawned
+/// task.  The fix is to give each imp its own [`rand::rngs::StdRng`], which is [`Send`], seeded
+/// from the operating system.  Now that the imps can roll dice properly, they can also get picky
+/// about the way they roll them.
+///
+/// The user selects a strategy per imp with the `pick` key of a [`crate::Temper`] in `Tardy.toml`:
+///
+/// ```toml
+/// [[imps]]
+/// pick = "shuffle_bag"
+///
+/// [[imps]]
+/// pick = { weighted = "author" }
+/// ```
+///
+/// The user's ratings reach every strategy.  The random picks lean on [`Weight::Rating`], so a
+/// disliked quote fades from view.  The strategies that take turns, and the remix, have no odds
+/// to lean on, so a quote rated at or below -[`RATING_CAP`] is buried instead, and they skip it,
+/// see [`Picker::is_buried`].
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    derive_more::Display,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Pick {
+    /// The `Random` variant picks any quote with equal probability, so repeats are possible.
+    /// Once the user has rated any of the quotes, the odds follow [`Weight::Rating`] instead.
+    #[default]
+    Random,
+    /// The `ShuffleBag` variant shuffles the quotes like a deck of cards and deals them out one
+    /// at a time, so no quote repeats until every quote has had its turn.  Buried quotes do not
+    /// go in the bag.
+    ShuffleBag,
+    /// The `Weighted` variant picks quotes at random, weighted according to the [`Weight`].
+    #[display("Weighted({_0})")]
+    Weighted(Weight),
+    /// The `RoundRobin` variant steps through the quotes in order, wrapping around at the end.
+    RoundRobin,
+    /// The `Daily` variant picks a quote of the day, determined by the date.  Every imp with the
+    /// same quotes will repeat the same quote until midnight.
+    Daily,
+    /// The `Remix` variant writes new pseudo-quotes from the corpus using a [`Remix`], with the
+    /// settings in the [`Riff`].
+    #[display("Remix")]
+    Remix(Riff),
+}
+
+/// The `Weight` enum determines the relative odds of each quote under [`Pick::Weighted`].
+#[derive(
+    Debug,
+    Default,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    derive_more::Display,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Weight {
+    /// The `Author` variant gives each author an equal chance of being picked, regardless of how
+    /// many quotes they have in the corpus.  Without it, the prolific authors drown out the rest.
+    /// Buried quotes are left out.
+    #[default]
+    Author,
+    /// The `Rating` variant favors quotes with a higher `rating`, see [`Quote::rating`].  Each
+    /// point of rating multiplies the odds by [`RATING_BASE`], so unrated quotes have weight one,
+    /// and disliked quotes fade from view without disappearing entirely.
+    Rating,
+}
+
+/// The `RATING_BASE` constant determines how strongly the rating of a quote affects its odds of
+/// being picked under [`Weight::Rating`].
+pub const RATING_BASE: f64 = 1.5;
+
+/// The `RATING_CAP` constant limits the magnitude of the rating used to weigh a quote, so that a
+/// single enthusiastic user cannot crowd out every other quote.  A quote rated at or below
+/// -`RATING_CAP` is buried, see [`Picker::is_buried`].
+pub const RATING_CAP: i32 = 10;
+
+/// The `Picker` struct carries out a [`Pick`] strategy, holding whatever state the strategy
+/// needs between picks.
+///
+/// * The `pick` field holds the strategy.
+/// * The `rng` field holds a [`rand::rngs::StdRng`], so the picker can be sent across threads.
+/// * The `bag` field holds the remaining indices for [`Pick::ShuffleBag`].
+/// * The `cursor` field holds the next index for [`Pick::RoundRobin`].
+/// * The `dealt` field holds the last index dealt from the bag, if any, so a fresh bag does not
+///   open with a repeat.
+/// * The `len` field holds the number of quotes in play when the state was last reset, not
+///   counting buried quotes.  If the number changes, we throw out the state and start over.
+/// * The `remix` field holds the [`Remix`] for [`Pick::Remix`], built from the corpus on the
+///   first pick.
+#[derive(Debug, Clone, derive_getters::Getters)]
+pub struct Picker {
+    pick: Pick,
+    #[getter(skip)]
+    rng: rand::rngs::StdRng,
+    #[getter(skip)]
+    bag: Vec<usize>,
+    #[getter(skip)]
+    cursor: usize,
+    #[getter(skip)]
+    dealt: Option<usize>,
+    #[getter(skip)]
+    len: usize,
+    #[getter(skip)]
+    remix: Option<Remix>,
+}
+
+impl Picker {
+    /// The `new` method creates a `Picker` for the strategy in `pick`, seeding the random number
+    /// generator from the operating system.
+    pub fn new(pick: Pick) -> Self {
+        Self::with_rng(pick, rand::rngs::StdRng::from_entropy())
+    }
+
+    /// The `seeded` method creates a `Picker` with a fixed `seed`, so the same sequence of picks
+    /// comes out every time.
+    pub fn seeded(pick: Pick, seed: u64) -> Self {
+        Self::with_rng(pick, rand::rngs::StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(pick: Pick, rng: rand::rngs::StdRng) -> Self {
+        Self {
+            pick,
+            rng,
+            bag: Vec::new(),
+            cursor: 0,
+            dealt: None,
+            len: 0,
+            remix: None,
+        }
+    }
+
+    /// The `reset` method discards the shuffle bag, rewinds the cursor and forgets the remix.
+    /// Call this method when the corpus changes, because the old indices may point at different
+    /// quotes, or past the end.
+    pub fn reset(&mut self) {
+        self.bag.clear();
+        self.cursor = 0;
+        self.dealt = None;
+        self.len = 0;
+        self.remix = None;
+    }
+
+    /// The `next` method picks the next quote from `quotes` according to the strategy in the
+    /// `pick` field.  Returns [`None`] if `quotes` is empty.
+    ///
+    /// Since a [`Pick::Remix`] writes brand new quotes that do not live in `quotes`, we return
+    /// the quote by value.  When the riff does not specify a seed, we seed the remix from the
+    /// random number generator of the picker, so that a seeded picker produces seeded remixes.
+    ///
+    /// Every strategy but [`Weight::Rating`] picks from the quotes in play, skipping the buried
+    /// ones.  If the user has buried every quote, we dig them all back up rather than go quiet.
+    pub fn next(&mut self, quotes: &Quotes) -> Option<Quote> {
+        if quotes.is_empty() {
+            return None;
+        }
+        let mut live = quotes
+            .iter()
+            .enumerate()
+            .filter(|(_, q)| !Self::is_buried(q))
+            .map(|(idx, _)| idx)
+            .collect::<Vec<usize>>();
+        if live.is_empty() {
+            live = (0..quotes.len()).collect();
+        }
+        if live.len() != self.len {
+            self.reset();
+            self.len = live.len();
+        }
+        let all = (0..quotes.len()).collect::<Vec<usize>>();
+        let idx = match self.pick.clone() {
+            Pick::Random if quotes.iter().any(|q| q.rating().is_some()) => {
+                self.weighted(quotes, &all, Weight::Rating)
+            }
+            Pick::Random => self.rng.gen_range(0..quotes.len()),
+            Pick::ShuffleBag => live[self.deal(live.len())],
+            Pick::Weighted(Weight::Rating) => self.weighted(quotes, &all, Weight::Rating),
+            Pick::Weighted(weight) => self.weighted(quotes, &live, weight),
+            Pick::RoundRobin => {
+                let idx = self.cursor % live.len();
+                self.cursor = idx + 1;
+                live[idx]
+            }
+            Pick::Daily => live[Self::daily(chrono::Local::now().date_naive(), live.len())],
+            Pick::Remix(riff) => {
+                let remix = self.remix.get_or_insert_with(|| {
+                    let seed = riff.seed().unwrap_or_else(|| self.rng.gen());
+                    let corpus = live.iter().map(|&idx| quotes[idx].clone()).collect();
+                    Remix::new(&Quotes::new(corpus), riff.with_seed(seed))
+                });
+                return remix.quote();
+            }
+        };
+        quotes.get(idx).cloned()
+    }
+
+    /// The `is_buried` method returns `true` if the user has rated `quote` at or below
+    /// -[`RATING_CAP`], low enough that the pickers without odds skip it altogether.
+    pub fn is_buried(quote: &Quote) -> bool {
+        quote.rating().is_some_and(|rating| rating <= -RATING_CAP)
+    }
+
+    /// The `deal` method pops the next index out of the shuffle bag, refilling and shuffling the
+    /// bag when it runs dry.  When refilling, we make sure the first quote of the new bag is not
+    /// the last quote of the old bag, so the seam between bags does not produce a repeat.
+    fn deal(&mut self, len: usize) -> usize {
+        if self.bag.is_empty() {
+            self.bag = (0..len).collect();
+            self.bag.shuffle(&mut self.rng);
+            if len > 1
+                && self
+                    .dealt
+                    .is_some_and(|last| self.bag.last() == Some(&last))
+            {
+                self.bag.swap(0, len - 1);
+            }
+        }
+        // The bag was filled above, and `len` is not zero.
+        let idx = self.bag.pop().unwrap_or_default();
+        self.dealt = Some(idx);
+        idx
+    }
+
+    /// The `weighted` method picks one of the indices in `picks` using a [`WeightedIndex`] built
+    /// from the weights described in [`Weight`].  If the weights are unusable for any reason, we
+    /// fall back on a uniform pick.
+    fn weighted(&mut self, quotes: &Quotes, picks: &[usize], weight: Weight) -> usize {
+        let weights = match weight {
+            Weight::Author => {
+                let mut counts = HashMap::new();
+                picks
+                    .iter()
+                    .for_each(|&idx| *counts.entry(quotes[idx].author()).or_insert(0usize) += 1);
+                picks
+                    .iter()
+                    .map(|&idx| 1.0 / counts[quotes[idx].author()] as f64)
+                    .collect::<Vec<f64>>()
+            }
+            Weight::Rating => picks
+                .iter()
+                .map(|&idx| {
+                    let rating = quotes[idx].rating().unwrap_or_default();
+                    RATING_BASE.powi(rating.clamp(-RATING_CAP, RATING_CAP))
+                })
+                .collect(),
+        };
+        let pos = match WeightedIndex::new(&weights) {
+            Ok(dist) => dist.sample(&mut self.rng),
+            Err(e) => {
+                tracing::warn!("Could not weigh quotes: {e}");
+                self.rng.gen_range(0..picks.len())
+            }
+        };
+        picks[pos]
+    }
+
+    /// The `daily` method maps `date` onto an index less than `len`.  We scramble the day count
+    /// using the finalizer from the SplitMix64 generator, so that consecutive days do not land on
+    /// consecutive quotes.
+    pub fn daily(date: chrono::NaiveDate, len: usize) -> usize {
+        use chrono::Datelike;
+        let mut z = (date.num_days_from_ce() as u64).wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z % len.max(1) as u64) as usize
+    }
+}
+
+impl Default for Picker {
+    fn default() -> Self {
+        Self::new(Pick::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quotes(count: usize) -> Quotes {
+        Quotes::new(
+            (0..count)
+                .map(|idx| Quote::new(None, idx.to_string()))
+                .collect(),
+        )
+    }
+
+    fn rated(count: usize, ratings: &[(usize, i32)]) -> Quotes {
+        let mut quotes = quotes(count);
+        for &(idx, rating) in ratings {
+            quotes[idx].set_rating(Some(rating));
+        }
+        quotes
+    }
+
+    #[test]
+    fn buried_quotes_skipped() {
+        let quotes = rated(3, &[(1, -RATING_CAP), (2, 1 - RATING_CAP)]);
+        let picks = [
+            Pick::ShuffleBag,
+            Pick::RoundRobin,
+            Pick::Daily,
+            Pick::Weighted(Weight::Author),
+        ];
+        for pick in picks {
+            let mut picker = Picker::seeded(pick.clone(), 7);
+            let seen = (0..20)
+                .filter_map(|_| picker.next(&quotes))
+                .map(|quote| quote.quote().clone())
+                .collect::<Vec<String>>();
+            assert!(
+                !seen.contains(&"1".to_string()),
+                "{pick} dealt a buried quote"
+            );
+            if pick != Pick::Daily {
+                assert!(
+                    seen.contains(&"2".to_string()),
+                    "{pick} skipped a live quote"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn all_buried_quotes_still_picked() {
+        let quotes = rated(2, &[(0, -RATING_CAP), (1, -RATING_CAP)]);
+        let mut picker = Picker::seeded(Pick::RoundRobin, 7);
+        let seen = (0..2)
+            .filter_map(|_| picker.next(&quotes))
+            .map(|quote| quote.quote().clone())
+            .collect::<Vec<String>>();
+        assert_eq!(seen, vec!["0", "1"]);
+    }
+
+    #[test]
+    fn shuffle_bag_first_pick_unbiased() {
+        let quotes = quotes(3);
+        let firsts = (0..300)
+            .filter_map(|seed| Picker::seeded(Pick::ShuffleBag, seed).next(&quotes))
+            .filter(|quote| quote.quote() == "0")
+            .count();
+        assert!(firsts > 0, "index 0 never leads a fresh bag");
+    }
+
+    #[test]
+    fn shuffle_bag_no_repeat_across_bags() {
+        let quotes = quotes(3);
+        for seed in 0..50 {
+            let mut picker = Picker::seeded(Pick::ShuffleBag, seed);
+            let picks = (0..30)
+                .filter_map(|_| picker.next(&quotes))
+                .collect::<Vec<Quote>>();
+            assert!(picks.windows(2).all(|pair| pair[0] != pair[1]));
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use crate::{Arrive, Blame, Excuse, DEFAULT_KEYMAP, KEYMAP_KEY};
+use convert_case::Casing;
+use strum::IntoEnumIterator;
+
+/// The `preset` module holds the [`Preset`] enum, the keymaps that ship with the program.
+///
+/// # Keymap Presets
+///
+/// A new user should not have to invent a key for every act before the program is any use.  The
+/// line `keymap = "vim"` at the top of `Tardy.toml` picks a preset, and the bindings in the rest
+/// of the file go on top of it, see [`crate::Cmd::from_config`].  Binding an act in `Tardy.toml`
+/// replaces the keys the preset gave that act, and a key the user binds to one act is taken away
+/// from any act the preset bound it to.  So the user only has to write down the bindings they
+/// want to change.
+///
+/// The presets cover the acts that stand on their own.  Modes are up to the user, since a preset
+/// cannot know which modes the user has in mind.
+#[derive(
+    Debug,
+    Default,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    strum_macros::EnumIter,
+    derive_more::Display,
+)]
+pub enum Preset {
+    /// The `Default` variant holds the bindings of the `Tardy.toml` that ships with the program,
+    /// from [`DEFAULT_KEYMAP`].
+    #[default]
+    Default,
+    /// The `Vim` variant holds bindings after the fashion of `vim`, like `: q` to quit and
+    /// `Ctrl+w n` for a new window.
+    Vim,
+    /// The `Emacs` variant holds bindings after the fashion of `emacs`, like `Ctrl+x Ctrl+c` to
+    /// quit and `Ctrl+x 5 2` for a new frame.
+    Emacs,
+    /// The `OneHanded` variant keeps every binding within reach of the left hand, leaving the
+    /// right hand on the mouse.
+    OneHanded,
+}
+
+/// The `VIM_KEYMAP` constant holds the bindings of [`Preset::Vim`].
+pub const VIM_KEYMAP: [(&str, &str); 7] = [
+    ("exit", ": q"),
+    ("new_window", "Ctrl+w n"),
+    ("close_window", "Ctrl+w c"),
+    ("upvote", "Ctrl+a"),
+    ("downvote", "Ctrl+x"),
+    ("rebind", ": m"),
+    ("show_help", ": h"),
+];
+
+/// The `EMACS_KEYMAP` constant holds the bindings of [`Preset::Emacs`].
+pub const EMACS_KEYMAP: [(&str, &str); 7] = [
+    ("exit", "Ctrl+x Ctrl+c"),
+    ("new_window", "Ctrl+x 5 2"),
+    ("close_window", "Ctrl+x 5 0"),
+    ("upvote", "Ctrl+c +"),
+    ("downvote", "Ctrl+c -"),
+    ("rebind", "Ctrl+c r"),
+    ("show_help", "Ctrl+h b"),
+];
+
+/// The `ONE_HANDED_KEYMAP` constant holds the bindings of [`Preset::OneHanded`].
+pub const ONE_HANDED_KEYMAP: [(&str, &str); 7] = [
+    ("exit", "Escape"),
+    ("new_window", "w"),
+    ("close_window", "x"),
+    ("upvote", "e"),
+    ("downvote", "d"),
+    ("rebind", "F2"),
+    ("show_help", "F1"),
+];
+
+impl Preset {
+    /// The `snake` method returns the name of the preset in snake case, as it appears in
+    /// `Tardy.toml`.
+    pub fn snake(&self) -> String {
+        self.to_string().to_case(convert_case::Case::Snake)
+    }
+
+    /// The `bindings` method pairs the snake case name of each act with the keys the preset binds
+    /// to it, in the same form as [`DEFAULT_KEYMAP`].
+    pub fn bindings(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::Default => &DEFAULT_KEYMAP,
+            Self::Vim => &VIM_KEYMAP,
+            Self::Emacs => &EMACS_KEYMAP,
+            Self::OneHanded => &ONE_HANDED_KEYMAP,
+        }
+    }
+
+    /// The `from_name` method finds the preset called `name`.  We compare names in snake case, so
+    /// `one_handed`, `one-handed` and `OneHanded` all find the same preset.
+    ///
+    /// Will [`Excuse::UnknownPreset`] if no preset goes by `name`.
+    pub fn from_name(name: &str) -> Arrive<Self> {
+        let name = name.to_case(convert_case::Case::Snake);
+        Self::iter()
+            .find(|preset| preset.snake() == name)
+            .ok_or(Blame::Excuse(Excuse::UnknownPreset(name)))
+    }
+
+    /// The `from_config` method returns the preset named by the `keymap` key of `config`, or the
+    /// [`Preset::Default`] if the key is missing or names no preset.  The diagnostics for a bad
+    /// name come from [`crate::Cmd::from_config`], so we keep quiet about it here.
+    pub fn from_config(config: &config::Config) -> Self {
+        config
+            .get_string(KEYMAP_KEY)
+            .ok()
+            .and_then(|name| Self::from_name(&name).ok())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_normalise() {
+        for name in ["one_handed", "one-handed", "OneHanded", "oneHanded"] {
+            assert_eq!(
+                Preset::from_name(name).unwrap(),
+                Preset::OneHanded,
+                "{name}"
+            );
+        }
+        assert_eq!(Preset::from_name("Vim").unwrap(), Preset::Vim);
+    }
+
+    #[test]
+    fn unknown_name_fails() {
+        assert_eq!(
+            Preset::from_name("Nano"),
+            Err(Blame::Excuse(Excuse::UnknownPreset("nano".to_string())))
+        );
+    }
+
+    #[test]
+    fn every_preset_binds_every_act_once() {
+        for preset in Preset::iter() {
+            let cmds = crate::Cmd::preset(preset);
+            assert_eq!(cmds.bindings().len(), preset.bindings().len(), "{preset}");
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use crate::{Act, Arrive, Cue, Routine};
+use std::collections::BTreeMap;
+use std::time::Instant;
+use std::{fs, path};
+
+/// The `register` module holds the [`Recorder`] struct, which writes down the acts of the user
+/// as they happen, and the [`Registers`] struct, where finished recordings wait to be replayed.
+///
+/// # Recording and Replay
+///
+/// Macros in `Tardy.toml` are fine for chores the user can plan ahead, but sometimes it is easier
+/// to show the program what to do than to write it down.  The key bound to [`Act::Record`] starts
+/// a [`Recorder`], which keeps every act dispatched by [`crate::App::act`] from then on, along
+/// with the time between them.  The key bound to [`Act::StopRecording`] stops the recorder, and
+/// keeps the recording in a register as a [`Routine`], replacing whatever the register held
+/// before.  The key bound to [`Act::Replay`] plays the routine in a register, pauses and all,
+/// through the same dispatcher as the macros, so any key cancels a replay too.
+///
+/// Registers have names, so the user can keep several recordings around.  Without a name, the
+/// acts use the [`DEFAULT_REGISTER`].  A named register takes a `[[binds]]` table:
+///
+/// ```toml
+/// record = "F5"
+/// stop_recording = "F6"
+/// replay = "F7"
+///
+/// [[binds]]
+/// keys = "Ctrl+F6"
+/// act = "stop_recording"
+/// register = "a"
+///
+/// [[binds]]
+/// keys = "Ctrl+F7"
+/// act = "replay"
+/// register = "a"
+/// ```
+///
+/// The `Registers` persist as JSON to the file named by the `path` field of the `[recordings]`
+/// table, or [`REGISTERS_PATH`] by default, so recordings outlive the session.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    PartialEq,
+    Eq,
+    derive_new::new,
+    derive_more::Deref,
+    derive_more::DerefMut,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub struct Registers(BTreeMap<String, Routine>);
+
+/// The `Recorder` struct holds a recording in progress.
+///
+/// * The `cues` field holds the acts recorded so far, with the pauses between them.
+/// * The `last` field holds the time of the last act recorded, or the start of the recording.
+#[derive(Debug, Clone, PartialEq, Eq, derive_getters::Getters)]
+pub struct Recorder {
+    cues: Vec<Cue>,
+    last: Instant,
+}
+
+/// The `DEFAULT_REGISTER` constant names the register used by acts that do not name one.
+pub const DEFAULT_REGISTER: &str = "default";
+
+/// The `REGISTERS_PATH` constant holds the default location of the [`Registers`], relative to
+/// the working directory, same as `Tardy.toml`.
+pub const REGISTERS_PATH: &str = "tardy_registers.json";
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Recorder {
+    /// The `new` method starts a recording, with the clock running from now.
+    pub fn new() -> Self {
+        Self {
+            cues: Vec::new(),
+            last: Instant::now(),
+        }
+    }
+
+    /// The `record` method adds `act` to the recording, after a pause as long as the time since
+    /// the last act.  Acts that control recording and replay would make a mess of a replay, so
+    /// they are left out, see [`Act::is_recordable`].  The acts of a macro or replay are recorded
+    /// one by one as they play, rather than as the act that started them.
+    pub fn record(&mut self, act: &Act) {
+        if !act.is_recordable() {
+            return;
+        }
+        let now = Instant::now();
+        let wait = u64::try_from((now - self.last).as_millis()).unwrap_or(u64::MAX);
+        if wait > 0 {
+            self.cues.push(Cue::Wait(wait));
+        }
+        self.cues.push(Cue::Act(act.clone()));
+        self.last = now;
+        tracing::trace!("Recorded: {act:?}");
+    }
+
+    /// The `finish` method ends the recording, returning it as a [`Routine`].  The pause before
+    /// the first act goes, since nobody wants a replay to sit and think before it starts.
+    pub fn finish(self) -> Routine {
+        let mut cues = self.cues;
+        if let Some(Cue::Wait(_)) = cues.first() {
+            cues.remove(0);
+        }
+        Routine::new(cues)
+    }
+}
+
+impl Registers {
+    /// The `path` method reads the location of the registers file from the `path` field of the
+    /// `[recordings]` table in `config`, falling back on [`REGISTERS_PATH`].
+    pub fn path(config: &config::Config) -> path::PathBuf {
+        config
+            .get_string("recordings.path")
+            .unwrap_or_else(|_| REGISTERS_PATH.to_string())
+            .into()
+    }
+
+    /// The `load` method reads the `Registers` from the JSON file at `path`.  A missing file
+    /// means nothing has been recorded yet, so we return empty registers.
+    ///
+    /// Will [`crate::Blame::Io`] if the file exists but cannot be read, and
+    /// [`crate::Blame::Json`] if the contents are not valid registers.
+    pub fn load(path: &path::Path) -> Arrive<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// The `save` method writes the `Registers` to `path` as JSON, creating any missing parent
+    /// directories.
+    pub fn save(&self, path: &path::Path) -> Arrive<()> {
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// The `name` method returns the name of `register`, or the [`DEFAULT_REGISTER`] if it has
+    /// none.
+    pub fn name(register: Option<&str>) -> &str {
+        register.unwrap_or(DEFAULT_REGISTER)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn scratch(name: &str) -> path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("tardy-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn skips_acts_that_control_recording() {
+        let mut recorder = Recorder::new();
+        recorder.record(&Act::Record);
+        recorder.record(&Act::StopRecording { register: None });
+        recorder.record(&Act::Replay { register: None });
+        assert!(recorder.cues().is_empty());
+        recorder.record(&Act::CloseWindow);
+        assert_eq!(recorder.cues().last(), Some(&Cue::Act(Act::CloseWindow)));
+    }
+
+    #[test]
+    fn finish_drops_leading_wait() {
+        let mut recorder = Recorder::new();
+        recorder.last -= Duration::from_millis(50);
+        recorder.record(&Act::CloseWindow);
+        recorder.last -= Duration::from_millis(50);
+        recorder.record(&Act::Exit);
+        assert!(matches!(recorder.cues()[0], Cue::Wait(_)));
+        let routine = recorder.finish();
+        assert_eq!(routine.len(), 3);
+        assert_eq!(routine[0], Cue::Act(Act::CloseWindow));
+        assert!(matches!(routine[1], Cue::Wait(ms) if ms >= 50));
+        assert_eq!(routine[2], Cue::Act(Act::Exit));
+    }
+
+    #[test]
+    fn save_load_round_trip() {
+        let path = scratch("registers").join("nested").join("registers.json");
+        let mut registers = Registers::default();
+        registers.insert(
+            DEFAULT_REGISTER.to_string(),
+            Routine::new(vec![
+                Cue::Act(Act::NewWindow { count: Some(2) }),
+                Cue::Wait(250),
+                Cue::Act(Act::CloseWindow),
+            ]),
+        );
+        registers.insert("a".to_string(), Routine::default());
+        registers.save(&path).unwrap();
+        assert_eq!(Registers::load(&path).unwrap(), registers);
+    }
+
+    #[test]
+    fn missing_file_is_empty() {
+        let path = scratch("registers-missing").join("registers.json");
+        assert_eq!(Registers::load(&path).unwrap(), Registers::default());
+    }
+}
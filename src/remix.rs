@@ -0,0 +1,245 @@
+use crate::{Quote, Quotes};
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, HashSet};
+
+/// The `remix` module holds the [`Remix`] struct, which writes new pseudo-quotes from old ones
+/// using a word-level Markov chain, and the [`Riff`] struct, which holds the settings for a remix.
+///
+/// # Remixing the Classics
+///
+/// Sixteen hundred quotes sounds like a lot, until the imps have repeated each one a dozen
+/// times.  Rather than hunting down more quotes, we can teach the imps to make up their own.  A
+/// Markov chain records, for every run of `order` consecutive words in the corpus, the words that
+/// followed that run.  To write a new quote, we start from the opening words of a real quote, and
+/// keep picking a plausible next word until we reach a word that ended a quote.  With an order of
+/// one, the results are mostly gibberish.  With an order of three, the results are mostly the
+/// original quotes.  Two is the sweet spot for inspirational nonsense.
+///
+/// Pass a seed to [`Remix::seeded`] to get the same sequence of quotes every time, which is handy
+/// for settling arguments about which imp said what.
+///
+/// To let an imp remix its quotes, use the [`crate::Pick::Remix`] strategy in `Tardy.toml`:
+///
+/// ```toml
+/// [[imps]]
+/// pick = { remix = { order = 2, mashup = true } }
+/// ```
+#[derive(Debug, Clone, derive_getters::Getters)]
+pub struct Remix {
+    /// Settings for the remix.
+    riff: Riff,
+    /// Maps each run of `order` words to the words that may follow, where [`None`] marks the end
+    /// of a quote.
+    #[getter(skip)]
+    chains: HashMap<Vec<String>, Vec<Option<String>>>,
+    /// Opening words of each quote in the corpus.
+    #[getter(skip)]
+    starts: Vec<Vec<String>>,
+    /// Authors in the corpus, used for mashups.
+    #[getter(skip)]
+    authors: Vec<String>,
+    /// Original quotes, so we can avoid passing one off as a remix.
+    #[getter(skip)]
+    originals: HashSet<String>,
+    /// Random number generator, seeded from `riff` when a seed is present.
+    #[getter(skip)]
+    rng: rand::rngs::StdRng,
+}
+
+/// The `Riff` struct holds the settings for a [`Remix`].
+///
+/// * The `order` field holds the number of words of context used to pick the next word.
+///   Defaults to [`REMIX_ORDER`].  An order of zero is treated as one.
+/// * The `seed` field holds an optional seed for the random number generator.
+/// * The `mashup` field indicates whether to attribute remixes to a mashup of two authors from
+///   the corpus, like `Yogi Twain`.  Otherwise, remixes are attributed to nobody.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    derive_getters::Getters,
+    derive_setters::Setters,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[setters(prefix = "with_", into)]
+#[serde(default)]
+pub struct Riff {
+    order: usize,
+    seed: Option<u64>,
+    mashup: bool,
+}
+
+impl Default for Riff {
+    fn default() -> Self {
+        Self {
+            order: REMIX_ORDER,
+            seed: None,
+            mashup: false,
+        }
+    }
+}
+
+/// The `REMIX_ORDER` constant holds the default number of words of context in a [`Remix`].
+pub const REMIX_ORDER: usize = 2;
+
+/// The `REMIX_WORDS` constant limits the length of a remix, in case the chain finds a loop.
+pub const REMIX_WORDS: usize = 60;
+
+/// The `REMIX_TRIES` constant limits the number of attempts [`Remix::quote`] makes to write
+/// something that is not already in the corpus.
+pub const REMIX_TRIES: usize = 10;
+
+impl Remix {
+    /// The `new` method builds a `Remix` from `quotes` using the settings in `riff`.  When `riff`
+    /// holds a seed, the remix is reproducible, otherwise we seed from the operating system.
+    pub fn new(quotes: &Quotes, riff: Riff) -> Self {
+        let rng = match riff.seed {
+            Some(seed) => rand::rngs::StdRng::seed_from_u64(seed),
+            None => rand::rngs::StdRng::from_entropy(),
+        };
+        let order = riff.order.max(1);
+        let mut chains: HashMap<Vec<String>, Vec<Option<String>>> = HashMap::new();
+        let mut starts = Vec::new();
+        let mut authors = HashSet::new();
+        let mut originals = HashSet::new();
+        for quote in quotes.iter() {
+            if let Some(author) = quote.author() {
+                authors.insert(author.clone());
+            }
+            let words = quote
+                .quote()
+                .split_whitespace()
+                .map(String::from)
+                .collect::<Vec<String>>();
+            if words.is_empty() {
+                continue;
+            }
+            originals.insert(words.join(" "));
+            let prefix = order.min(words.len());
+            starts.push(words[..prefix].to_vec());
+            for idx in 0..=(words.len() - prefix) {
+                let key = words[idx..idx + prefix].to_vec();
+                let next = words.get(idx + prefix).cloned();
+                chains.entry(key).or_default().push(next);
+            }
+        }
+        let mut authors = authors.into_iter().collect::<Vec<String>>();
+        // Sort so that a seeded remix does not depend on the iteration order of the set.
+        authors.sort();
+        Self {
+            riff,
+            chains,
+            starts,
+            authors,
+            originals,
+            rng,
+        }
+    }
+
+    /// The `seeded` method builds a `Remix` from `quotes` with the given `order` and `seed`, so
+    /// the same quotes come out in the same order every time.
+    pub fn seeded(quotes: &Quotes, order: usize, seed: u64) -> Self {
+        Self::new(quotes, Riff::default().with_order(order).with_seed(seed))
+    }
+
+    /// The `quote` method writes a new pseudo-quote.  Returns [`None`] if the corpus was empty.
+    ///
+    /// We make up to [`REMIX_TRIES`] attempts to write a quote that does not already appear in
+    /// the corpus, keeping the last attempt if they all turn out to be originals.  Small corpora
+    /// do not leave the chain much room to wander.
+    pub fn quote(&mut self) -> Option<Quote> {
+        let mut text = self.riff_once()?;
+        for _ in 1..REMIX_TRIES {
+            if !self.originals.contains(&text) {
+                break;
+            }
+            text = self.riff_once()?;
+        }
+        let author = if self.riff.mashup {
+            self.mashup()
+        } else {
+            None
+        };
+        Some(Quote::new(author, text))
+    }
+
+    /// The `riff_once` method walks the chain once, from a random start to an end of quote, or
+    /// [`REMIX_WORDS`], whichever comes first.
+    fn riff_once(&mut self) -> Option<String> {
+        let mut words = self.starts.choose(&mut self.rng)?.clone();
+        let order = self.riff.order.max(1);
+        while words.len() < REMIX_WORDS {
+            let key = words[words.len().saturating_sub(order)..].to_vec();
+            let next = self
+                .chains
+                .get(&key)
+                .and_then(|choices| choices.choose(&mut self.rng))
+                .cloned()
+                .flatten();
+            match next {
+                Some(word) => words.push(word),
+                None => break,
+            }
+        }
+        Some(words.join(" "))
+    }
+
+    /// The `mashup` method combines the first name of one author with the last name of another.
+    /// Returns [`None`] if the corpus has no attributed quotes.
+    fn mashup(&mut self) -> Option<String> {
+        if self.authors.is_empty() {
+            return None;
+        }
+        let first = &self.authors[self.rng.gen_range(0..self.authors.len())];
+        let last = &self.authors[self.rng.gen_range(0..self.authors.len())];
+        let first = first.split_whitespace().next().unwrap_or_default();
+        let last = last.split_whitespace().last().unwrap_or_default();
+        if first == last {
+            Some(first.to_string())
+        } else {
+            Some(format!("{first} {last}"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Pick, Picker};
+
+    fn takes(mut next: impl FnMut() -> Option<Quote>) -> Vec<Quote> {
+        (0..20).filter_map(|_| next()).collect()
+    }
+
+    #[test]
+    fn same_seed_same_remixes() {
+        let quotes = Quotes::builtin();
+        let riff = Riff::default().with_seed(42u64).with_mashup(true);
+        let mut first = Remix::new(&quotes, riff.clone());
+        let mut second = Remix::new(&quotes, riff);
+        let first = takes(|| first.quote());
+        assert_eq!(first.len(), 20);
+        assert_eq!(first, takes(|| second.quote()));
+        let mut other = Remix::seeded(&quotes, REMIX_ORDER, 43);
+        let mut again = Remix::seeded(&quotes, REMIX_ORDER, 43);
+        assert_eq!(takes(|| other.quote()), takes(|| again.quote()));
+    }
+
+    #[test]
+    fn seeded_picker_remixes_alike() {
+        let quotes = Quotes::builtin();
+        let pick = Pick::Remix(Riff::default());
+        let mut first = Picker::seeded(pick.clone(), 7);
+        let mut second = Picker::seeded(pick, 7);
+        assert_eq!(
+            takes(|| first.next(&quotes)),
+            takes(|| second.next(&quotes))
+        );
+    }
+}
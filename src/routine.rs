@@ -0,0 +1,255 @@
+use crate::{Act, Arrive, Blame, Excuse, Hijinks};
+use std::time::Duration;
+use strum::IntoEnumIterator;
+use tokio::time;
+use winit::{event_loop, window};
+
+/// The `routine` module holds the [`Routine`] struct, a named series of acts defined by the user,
+/// and the [`Cue`] enum, a single step of a routine.
+///
+/// # Macros
+///
+/// Some things take more than one act, like opening a couple of windows and giving one of them a
+/// sensible size.  The `[macros]` table of `Tardy.toml` strings acts together under a name of the
+/// user's choosing, with optional pauses between the steps:
+///
+/// ```toml
+/// [macros]
+/// chaos = [
+///     "new_window",
+///     { wait = 500 },
+///     "new_window",
+///     { act = "resize_window", width = 800, height = 600 },
+/// ]
+/// ```
+///
+/// Each step is the name of an act, a table naming an act with parameters, like the `[[binds]]`
+/// tables described in [`Act`], or a table with a `wait` in milliseconds.  Once defined, the name
+/// of the macro binds like the name of any act, as in `chaos = "Ctrl+c"`, and binds to
+/// [`Act::Macro`] behind the scenes.
+///
+/// Playing a routine is the job of [`Routine::play`], which runs as a [`tokio`] task, sending
+/// each act back to the event loop as [`Hijinks::Cue`], so the acts of a macro take the same path
+/// through [`crate::App::act`] as the acts bound to keys.  Pressing any key while a macro plays
+/// cancels the rest of it.  A macro may not play other macros, which keeps us clear of a macro
+/// that plays itself forever.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    PartialEq,
+    Eq,
+    derive_new::new,
+    derive_more::Deref,
+    derive_more::DerefMut,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub struct Routine(Vec<Cue>);
+
+/// The `Cue` enum holds a single step of a [`Routine`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Cue {
+    /// The `Act` variant performs the act it holds.
+    Act(Act),
+    /// The `Wait` variant pauses the routine for the number of milliseconds it holds.
+    Wait(u64),
+}
+
+/// The `WAIT_KEY` constant holds the field name of a pause in a [`Routine`].
+pub const WAIT_KEY: &str = "wait";
+
+impl Routine {
+    /// The `from_value` method reads the steps of the macro called `name` from `value`, a list of
+    /// steps, adding a diagnostic to `snags` for each bad step.  Bad steps are left out, so the
+    /// rest of the macro still plays.  The diagnostics are:
+    ///
+    /// * [`Excuse::Binding`] when `value` is not a list, or a step is neither a string nor a
+    ///   table, or a table that does not make an [`Act`], or when a step plays a macro.
+    /// * [`Excuse::UnknownAct`] when a string does not name an act that goes without parameters.
+    /// * [`Excuse::Parameter`] when an act fails [`Act::validate`].
+    pub fn from_value(name: &str, value: config::Value, snags: &mut Vec<Blame>) -> Self {
+        let name = format!("macros.{name}");
+        let steps = match value.clone().into_array() {
+            Ok(steps) => steps,
+            Err(_) => {
+                snags.push(Self::bad(&name, &value.to_string()));
+                return Self::default();
+            }
+        };
+        let mut cues = Vec::new();
+        for (idx, step) in steps.into_iter().enumerate() {
+            let name = format!("{name}[{idx}]");
+            match Self::cue(&name, step) {
+                Ok(cue) => cues.push(cue),
+                Err(e) => snags.push(e),
+            }
+        }
+        Self(cues)
+    }
+
+    /// The `cue` method reads a single `step` of a macro, identified by `name` in diagnostics.
+    fn cue(name: &str, step: config::Value) -> Arrive<Cue> {
+        let act = match step.kind.clone() {
+            config::ValueKind::String(text) => Act::iter()
+                .find(|act| act.snake() == text && act.validate().is_ok())
+                .ok_or(Blame::Excuse(Excuse::UnknownAct(format!(
+                    "{name} = {text}"
+                ))))?,
+            config::ValueKind::Table(fields) if fields.contains_key(WAIT_KEY) => {
+                let wait = fields[WAIT_KEY].clone();
+                return wait
+                    .clone()
+                    .into_uint()
+                    .map(Cue::Wait)
+                    .map_err(|_| Self::bad(name, &wait.to_string()));
+            }
+            config::ValueKind::Table(_) => step
+                .try_deserialize::<Act>()
+                .map_err(|e| Self::bad(name, &e.to_string()))?,
+            _ => return Err(Self::bad(name, &step.to_string())),
+        };
+        act.validate()?;
+        if let Act::Macro { .. } = act {
+            return Err(Self::bad(name, "macros cannot play other macros"));
+        }
+        Ok(Cue::Act(act))
+    }
+
+    /// The `bad` method describes a step `value` of the macro `name` that cannot be read.
+    fn bad(name: &str, value: &str) -> Blame {
+        Blame::Excuse(Excuse::Binding {
+            key: name.to_string(),
+            value: value.to_string(),
+        })
+    }
+
+    /// The `to_toml` method writes the steps of the routine back out in the form read by
+    /// [`Routine::from_value`].
+    pub fn to_toml(&self) -> toml::Value {
+        let steps = self
+            .iter()
+            .filter_map(|cue| match cue {
+                Cue::Act(act) if act.is_plain() => Some(toml::Value::String(act.snake())),
+                Cue::Act(act) => toml::Value::try_from(act).ok(),
+                Cue::Wait(ms) => {
+                    let mut wait = toml::Table::new();
+                    let ms = i64::try_from(*ms).unwrap_or(i64::MAX);
+                    wait.insert(WAIT_KEY.to_string(), toml::Value::Integer(ms));
+                    Some(toml::Value::Table(wait))
+                }
+            })
+            .collect();
+        toml::Value::Array(steps)
+    }
+
+    /// The `play` method performs the routine as an async process, sending each act to the event
+    /// loop through `proxy` as a [`Hijinks::Cue`] aimed at the window `id`, and sleeping through
+    /// each pause.  To cancel the routine, abort the task running it.
+    ///
+    /// Will [`crate::Blame::EventLoopClosed`] if the event loop closes before the routine ends.
+    pub async fn play(
+        self,
+        id: window::WindowId,
+        proxy: event_loop::EventLoopProxy<Hijinks>,
+    ) -> Arrive<()> {
+        for cue in self.0 {
+            match cue {
+                Cue::Act(act) => proxy.send_event(Hijinks::Cue(id, act))?,
+                Cue::Wait(ms) => time::sleep(Duration::from_millis(ms)).await,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn steps(src: &str) -> config::Value {
+        config::Config::builder()
+            .add_source(config::File::from_str(src, config::FileFormat::Toml))
+            .build()
+            .unwrap()
+            .get::<config::Value>("steps")
+            .unwrap()
+    }
+
+    fn routine(src: &str) -> (Routine, Vec<Blame>) {
+        let mut snags = Vec::new();
+        let routine = Routine::from_value("test", steps(src), &mut snags);
+        (routine, snags)
+    }
+
+    #[test]
+    fn reads_each_kind_of_step() {
+        let (routine, snags) = routine(
+            "steps = [\"new_window\", { wait = 500 }, \
+             { act = \"resize_window\", width = 800, height = 600 }]",
+        );
+        assert!(snags.is_empty(), "{snags:?}");
+        assert_eq!(
+            routine,
+            Routine::new(vec![
+                Cue::Act(Act::NewWindow { count: None }),
+                Cue::Wait(500),
+                Cue::Act(Act::ResizeWindow {
+                    width: 800,
+                    height: 600
+                }),
+            ])
+        );
+    }
+
+    #[test]
+    fn rejects_nested_macros() {
+        let (routine, snags) = routine("steps = [{ act = \"macro\", name = \"again\" }]");
+        assert!(routine.is_empty());
+        assert!(
+            matches!(&snags[..], [Blame::Excuse(Excuse::Binding { key, .. })] if key == "macros.test[0]"),
+            "{snags:?}"
+        );
+    }
+
+    #[test]
+    fn drops_bad_steps() {
+        let (routine, snags) = routine(
+            "steps = [\"new_window\", \"new_widow\", { wait = -1 }, 7, \
+             { act = \"resize_window\", width = 1, height = 1 }, \"exit\"]",
+        );
+        assert_eq!(
+            routine,
+            Routine::new(vec![
+                Cue::Act(Act::NewWindow { count: None }),
+                Cue::Act(Act::Exit),
+            ])
+        );
+        assert_eq!(snags.len(), 4, "{snags:?}");
+        assert!(matches!(snags[0], Blame::Excuse(Excuse::UnknownAct(_))));
+        assert!(matches!(snags[1], Blame::Excuse(Excuse::Binding { .. })));
+        assert!(matches!(snags[2], Blame::Excuse(Excuse::Binding { .. })));
+        assert!(matches!(snags[3], Blame::Excuse(Excuse::Parameter(_))));
+    }
+
+    #[test]
+    fn not_a_list() {
+        let (routine, snags) = routine("steps = \"new_window\"");
+        assert!(routine.is_empty());
+        assert_eq!(snags.len(), 1);
+    }
+
+    #[test]
+    fn toml_round_trip() {
+        let routine = Routine::new(vec![
+            Cue::Act(Act::NewWindow { count: Some(2) }),
+            Cue::Wait(250),
+            Cue::Act(Act::CloseWindow),
+        ]);
+        let mut doc = toml::Table::new();
+        doc.insert("steps".to_string(), routine.to_toml());
+        let src = toml::to_string(&doc).unwrap();
+        assert_eq!(self::routine(&src), (routine, Vec::new()));
+    }
+}
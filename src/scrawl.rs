@@ -0,0 +1,258 @@
+use crate::{Arrive, Blame, Excuse, Graffiti};
+use rand::Rng;
+use std::str::FromStr;
+
+/// The `scrawl` module holds the [`Scrawl`] struct, a message template for the quotes imps send
+/// to the logs, and the [`Scrawls`] struct, the collection of templates read from `Tardy.toml`.
+///
+/// # Scrawling Graffiti
+///
+/// The message format `"{name} says: {quote}"` used to be baked into [`crate::Imp::vandalize`],
+/// with the quote itself formatted by [`crate::Quote::graffiti`].  Now the imp sends the raw
+/// ingredients to the application as a [`Graffiti`], and the application fills in a template
+/// chosen at random from the `templates` list under the `[vandalize]` table of `Tardy.toml`:
+///
+/// ```toml
+/// [vandalize]
+/// templates = [
+///     "{name} says: '{quote}' - {author}",
+///     "[{time}] Imp #{id} scrawls '{quote}' on window {windows}.",
+/// ]
+/// ```
+///
+/// The available placeholders are:
+///
+/// * `{name}` - The name of the imp.
+/// * `{id}` - The number of the imp, assigned by the [`crate::ImpKing`].
+/// * `{author}` - The author of the quote, or `Unknown`.
+/// * `{quote}` - The text of the quote.
+/// * `{time}` - The local time the imp picked the quote, as `HH:MM:SS`.
+/// * `{windows}` - The number of windows open when the message arrives.
+///
+/// Use `{{` and `}}` for literal braces.  A template with any other placeholder is rejected when
+/// the configuration loads, rather than producing a confusing message later.
+#[derive(Debug, Clone, PartialEq, Eq, derive_getters::Getters)]
+pub struct Scrawl {
+    /// The template as written by the user.
+    template: String,
+    /// The template broken into literal text and placeholders.
+    #[getter(skip)]
+    pieces: Vec<Piece>,
+}
+
+/// The `Piece` enum holds either literal text or a placeholder from a [`Scrawl`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Piece {
+    Text(String),
+    Slot(Slot),
+}
+
+/// The `Slot` enum names each placeholder we know how to fill.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, strum_macros::EnumString)]
+#[strum(serialize_all = "snake_case")]
+enum Slot {
+    Name,
+    Id,
+    Author,
+    Quote,
+    Time,
+    Windows,
+}
+
+/// The `DEFAULT_SCRAWL` constant holds the template used when the user does not provide any,
+/// which reproduces the message format from before templates existed.
+pub const DEFAULT_SCRAWL: &str = "{name} says: '{quote}' - {author}";
+
+/// The `TIME_FORMAT` constant holds the [`chrono::format::strftime`] format used for `{time}`.
+pub const TIME_FORMAT: &str = "%H:%M:%S";
+
+impl Scrawl {
+    /// The `render` method fills the placeholders in the template using the contents of
+    /// `graffiti`, and `windows` for the number of open windows.
+    pub fn render(&self, graffiti: &Graffiti, windows: usize) -> String {
+        let mut msg = String::new();
+        for piece in &self.pieces {
+            match piece {
+                Piece::Text(text) => msg.push_str(text),
+                Piece::Slot(slot) => match slot {
+                    Slot::Name => msg.push_str(graffiti.imp()),
+                    Slot::Id => msg.push_str(&graffiti.id().to_string()),
+                    Slot::Author => {
+                        msg.push_str(graffiti.quote().author().as_deref().unwrap_or("Unknown"))
+                    }
+                    Slot::Quote => msg.push_str(graffiti.quote().quote()),
+                    Slot::Time => msg.push_str(&graffiti.time().format(TIME_FORMAT).to_string()),
+                    Slot::Windows => msg.push_str(&windows.to_string()),
+                },
+            }
+        }
+        msg
+    }
+}
+
+/// Parsing a template walks the characters looking for braces.  A doubled brace is a literal
+/// brace, and a single `{` opens a placeholder that must close with `}` and name a known slot.
+///
+/// Will [`Excuse::Placeholder`] if a placeholder has an unknown name, and [`Excuse::Unclosed`]
+/// if a placeholder never closes.
+impl FromStr for Scrawl {
+    type Err = Blame;
+
+    fn from_str(template: &str) -> Arrive<Self> {
+        let mut pieces = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    let mut closed = false;
+                    for c in chars.by_ref() {
+                        if c == '}' {
+                            closed = true;
+                            break;
+                        }
+                        name.push(c);
+                    }
+                    if !closed {
+                        return Err(Blame::Excuse(Excuse::Unclosed(template.to_string())));
+                    }
+                    let slot = Slot::from_str(name.trim())
+                        .map_err(|_| Blame::Excuse(Excuse::Placeholder(name.clone())))?;
+                    if !text.is_empty() {
+                        pieces.push(Piece::Text(std::mem::take(&mut text)));
+                    }
+                    pieces.push(Piece::Slot(slot));
+                }
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            pieces.push(Piece::Text(text));
+        }
+        Ok(Self {
+            template: template.to_string(),
+            pieces,
+        })
+    }
+}
+
+impl Default for Scrawl {
+    fn default() -> Self {
+        // The default template is known to be valid.
+        Self::from_str(DEFAULT_SCRAWL).expect("Default template is valid.")
+    }
+}
+
+/// The `Scrawls` struct is a wrapper around a vector of type [`Scrawl`].  The purpose of this
+/// struct is to hold the templates available to the application, and pick one at random for each
+/// message.  It is never empty, because the [`Default`] holds the [`DEFAULT_SCRAWL`].
+#[derive(Debug, Clone, PartialEq, Eq, derive_more::Deref)]
+pub struct Scrawls(Vec<Scrawl>);
+
+impl Scrawls {
+    /// The `from_config` method reads the `templates` list from the `[vandalize]` table of
+    /// `config`, parsing each template into a [`Scrawl`].  Returns the valid templates along with
+    /// an error for each template that failed to parse, so the caller can report them.  If no
+    /// templates are configured, or none are valid, we use the default.
+    pub fn from_config(config: &config::Config) -> (Self, Vec<Blame>) {
+        let templates = match config.get::<Vec<String>>("vandalize.templates") {
+            Ok(templates) => templates,
+            Err(config::ConfigError::NotFound(_)) => Vec::new(),
+            Err(e) => {
+                tracing::warn!("Could not read templates: {e}");
+                Vec::new()
+            }
+        };
+        let mut scrawls = Vec::new();
+        let mut problems = Vec::new();
+        for template in templates {
+            match Scrawl::from_str(&template) {
+                Ok(scrawl) => scrawls.push(scrawl),
+                Err(e) => problems.push(e),
+            }
+        }
+        if scrawls.is_empty() {
+            (Self::default(), problems)
+        } else {
+            (Self(scrawls), problems)
+        }
+    }
+
+    /// The `render` method fills in a template chosen at random, see [`Scrawl::render`].
+    pub fn render(&self, graffiti: &Graffiti, windows: usize) -> String {
+        let idx = rand::thread_rng().gen_range(0..self.len());
+        self[idx].render(graffiti, windows)
+    }
+}
+
+impl Default for Scrawls {
+    fn default() -> Self {
+        Self(vec![Scrawl::default()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Quote;
+
+    fn graffiti() -> Graffiti {
+        Graffiti::new(
+            3,
+            "Puck".to_string(),
+            Quote::new(None, "Hello.".to_string()),
+        )
+    }
+
+    #[test]
+    fn render_slots() {
+        let scrawl = Scrawl::from_str("{{{name}}} #{id}: '{quote}' - {author}, {windows}").unwrap();
+        assert_eq!(
+            scrawl.render(&graffiti(), 2),
+            "{Puck} #3: 'Hello.' - Unknown, 2"
+        );
+    }
+
+    #[test]
+    fn render_default() {
+        assert_eq!(
+            Scrawl::default().render(&graffiti(), 0),
+            "Puck says: 'Hello.' - Unknown"
+        );
+    }
+
+    #[test]
+    fn bad_templates_fail() {
+        assert!(matches!(
+            Scrawl::from_str("{nmae} says hi"),
+            Err(Blame::Excuse(Excuse::Placeholder(name))) if name == "nmae"
+        ));
+        assert!(matches!(
+            Scrawl::from_str("{name says hi"),
+            Err(Blame::Excuse(Excuse::Unclosed(_)))
+        ));
+    }
+
+    #[test]
+    fn bad_templates_fall_back() {
+        let config = config::Config::builder()
+            .add_source(config::File::from_str(
+                "[vandalize]\ntemplates = [\"{bogus}\"]",
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .unwrap();
+        let (scrawls, problems) = Scrawls::from_config(&config);
+        assert_eq!(scrawls, Scrawls::default());
+        assert_eq!(problems.len(), 1);
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{Act, Cmd, Sequence, DEFAULT_MODE};
+use std::collections::BTreeMap;
+
+/// The `sheet` module holds the [`Sheet`] enum, which renders the bindings of a [`Cmd`] for
+/// human eyes.
+///
+/// # Cheat Sheets
+///
+/// Between chords, sequences, modes and rebinding on the fly, it is easy to lose track of which
+/// key does what, and the `Debug` output of [`Cmd`] in the trace logs is no help to anyone.  A
+/// `Sheet` lists the bindings of each layer of the keymap, grouped by act, with the
+/// [`Act::title`] and [`Act::describe`] of each act.
+///
+/// The user can ask for a cheat sheet in two ways.  The key bound to [`Act::ShowHelp`] logs a
+/// plain text sheet, and `tardy keys` prints one in the format of choice, including
+/// [`Sheet::Toml`], which exports the keymap in the same shape as the bindings in `Tardy.toml`,
+/// see [`Cmd::to_toml`].
+#[derive(
+    Debug,
+    Default,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    derive_more::Display,
+    clap::ValueEnum,
+)]
+pub enum Sheet {
+    /// The `Text` variant renders aligned columns of plain text.
+    #[default]
+    Text,
+    /// The `Markdown` variant renders a Markdown table for each layer of the keymap.
+    Markdown,
+    /// The `Toml` variant renders the keymap as TOML.
+    Toml,
+}
+
+/// The `Row` type holds the keys bound to an act, its title and its description.
+type Row = (Vec<String>, String, String);
+
+impl Sheet {
+    /// The `render` method renders the bindings of `cmd` in the format of `self`.
+    pub fn render(&self, cmd: &Cmd) -> String {
+        match self {
+            Self::Text => Self::text(cmd),
+            Self::Markdown => Self::markdown(cmd),
+            Self::Toml => cmd.to_toml(),
+        }
+    }
+
+    /// The `layers` method lists the layers of `cmd` by name, starting with the global layer,
+    /// with the rows of each layer as the keys, title and description of each act.
+    fn layers(cmd: &Cmd) -> Vec<(String, Vec<Row>)> {
+        let mut layers = vec![("Global".to_string(), Self::rows(cmd.bindings()))];
+        for mode in cmd.mode_names() {
+            let rows = Self::rows(cmd.bindings_in(&mode));
+            layers.push((format!("Mode: {mode}"), rows));
+        }
+        layers
+    }
+
+    /// The `rows` method groups `bindings` by act, in the order of the [`Act`] variants.
+    fn rows(bindings: Vec<(Sequence, Act)>) -> Vec<Row> {
+        let mut acts = BTreeMap::<Act, Vec<String>>::new();
+        for (keys, act) in bindings {
+            acts.entry(act).or_default().push(keys.to_string());
+        }
+        acts.into_iter()
+            .map(|(act, keys)| (keys, act.title(), act.describe()))
+            .collect()
+    }
+
+    /// The `header` method names the current mode of `cmd`, if it is not the [`DEFAULT_MODE`].
+    fn header(cmd: &Cmd) -> Option<String> {
+        (cmd.mode() != DEFAULT_MODE).then(|| format!("Current mode: {}", cmd.mode()))
+    }
+
+    /// The `text` method renders the bindings of `cmd` as plain text, with the columns of each
+    /// layer lined up.
+    fn text(cmd: &Cmd) -> String {
+        let mut sheet = String::new();
+        if let Some(header) = Self::header(cmd) {
+            sheet.push_str(&format!("{header}\n\n"));
+        }
+        for (name, rows) in Self::layers(cmd) {
+            sheet.push_str(&format!("{name}\n"));
+            let rows = rows
+                .into_iter()
+                .map(|(keys, title, about)| (keys.join(", "), title, about))
+                .collect::<Vec<(String, String, String)>>();
+            let keys = rows.iter().map(|row| row.0.len()).max().unwrap_or(0);
+            let title = rows.iter().map(|row| row.1.len()).max().unwrap_or(0);
+            for (k, t, d) in rows {
+                sheet.push_str(&format!("  {k:<keys$}  {t:<title$}  {d}\n"));
+            }
+            sheet.push('\n');
+        }
+        sheet
+    }
+
+    /// The `markdown` method renders the bindings of `cmd` as Markdown, with a table for each
+    /// layer.  Keys go in code spans, so `Ctrl+x` does not turn into a stray bit of markup.
+    fn markdown(cmd: &Cmd) -> String {
+        let mut sheet = "# Key Bindings\n\n".to_string();
+        if let Some(header) = Self::header(cmd) {
+            sheet.push_str(&format!("{header}\n\n"));
+        }
+        for (name, rows) in Self::layers(cmd) {
+            sheet.push_str(&format!("## {name}\n\n"));
+            sheet.push_str("| Keys | Act | Description |\n| --- | --- | --- |\n");
+            for (k, t, d) in rows {
+                let k = k
+                    .iter()
+                    .map(|keys| format!("`{}`", keys.replace('|', "\\|")))
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                sheet.push_str(&format!("| {k} | {t} | {d} |\n"));
+            }
+            sheet.push('\n');
+        }
+        sheet
+    }
+}
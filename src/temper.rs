@@ -0,0 +1,174 @@
+use crate::{Pick, Quote, Quotes};
+
+/// The `temper` module holds the [`Temper`] struct, a behavior profile for an [`crate::Imp`], and
+/// the [`Sift`] struct, which narrows down the [`Quotes`] an imp is willing to repeat.
+///
+/// # Imps of Different Tempers
+///
+/// Up until now, every imp received a full clone of the same [`Quotes`], so they all sounded
+/// alike.  Imps have personalities too, and some of them only care about baseball.  The user can
+/// now describe imps in `Tardy.toml` using an array of tables under the `imps` key:
+///
+/// ```toml
+/// [[imps]]
+/// name = "Yogi's Imp"
+/// authors = ["Yogi Berra"]
+///
+/// [[imps]]
+/// tags = ["humor"]
+/// max_len = 80
+/// ```
+///
+/// The [`crate::ImpKing`] hands out tempers to imps in turn, wrapping around when there are more
+/// imps than tempers.  When the user does not describe any tempers, every imp gets the full
+/// corpus, same as before.
+///
+/// * The `name` field overrides the randomly generated name of the imp.
+/// * The `pick` field chooses the strategy the imp uses to pick quotes, see [`Pick`].
+/// * The remaining fields describe a [`Sift`], flattened into the same table for convenience.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    PartialEq,
+    Eq,
+    derive_getters::Getters,
+    derive_setters::Setters,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[setters(prefix = "with_", into)]
+#[serde(default)]
+pub struct Temper {
+    name: Option<String>,
+    pick: Pick,
+    #[serde(flatten)]
+    sift: Sift,
+}
+
+impl Temper {
+    /// The `quotes` method returns the subset of `quotes` that suits this temper, using
+    /// [`Sift::apply`].  If the sift is so picky that no quotes survive, we warn the user and
+    /// return the full corpus, because an imp with nothing to say is no imp at all.
+    pub fn quotes(&self, quotes: &Quotes) -> Quotes {
+        let subset = self.sift.apply(quotes);
+        if subset.is_empty() {
+            tracing::warn!("No quotes match {:?}, using all quotes.", self.sift);
+            quotes.clone()
+        } else {
+            subset
+        }
+    }
+}
+
+/// The `Tempers` struct is a wrapper around a vector of type [`Temper`], read from the `imps`
+/// key of `Tardy.toml`.  We use [`derive_more::Deref`] and [`derive_more::DerefMut`] for easy
+/// access to the underlying vector.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    PartialEq,
+    Eq,
+    derive_new::new,
+    derive_more::Deref,
+    derive_more::DerefMut,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub struct Tempers(Vec<Temper>);
+
+impl Tempers {
+    /// The `nth` method returns the temper assigned to the imp at position `idx`, cycling
+    /// through the available tempers.  Returns [`None`] when there are no tempers.
+    pub fn nth(&self, idx: usize) -> Option<&Temper> {
+        if self.is_empty() {
+            None
+        } else {
+            self.get(idx % self.len())
+        }
+    }
+}
+
+/// A missing `imps` key yields an empty `Tempers`, while a malformed one produces a warning
+/// before yielding an empty `Tempers`.
+impl From<&config::Config> for Tempers {
+    fn from(config: &config::Config) -> Self {
+        match config.get::<Vec<Temper>>("imps") {
+            Ok(tempers) => Self::new(tempers),
+            Err(config::ConfigError::NotFound(_)) => Self::default(),
+            Err(e) => {
+                tracing::warn!("Could not read imp tempers: {e}");
+                Self::default()
+            }
+        }
+    }
+}
+
+/// The `Sift` struct holds criteria for filtering [`Quotes`].  The purpose of this struct is to
+/// describe a subset of quotes in configuration, rather than in code.  For one-off queries, the
+/// methods on [`Quotes`] like [`Quotes::with_tag`] and [`Quotes::by_author`] may be more
+/// convenient.
+///
+/// A quote must satisfy every criterion that is present.  Within a list of values, matching any
+/// one value is enough, so `authors = ["Yogi Berra", "Mark Twain"]` selects quotes by either man.
+/// Text comparisons ignore case.
+///
+/// * The `authors` field lists acceptable authors.
+/// * The `tags` field lists acceptable tags.
+/// * The `keywords` field lists words or phrases, one of which must appear in the quote.
+/// * The `min_len` and `max_len` fields bound the number of characters in the quote.
+/// * The `category` field names the required category.
+/// * The `language` field names the required language.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    PartialEq,
+    Eq,
+    derive_getters::Getters,
+    derive_setters::Setters,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[setters(prefix = "with_", into)]
+#[serde(default)]
+pub struct Sift {
+    authors: Vec<String>,
+    tags: Vec<String>,
+    keywords: Vec<String>,
+    min_len: Option<usize>,
+    max_len: Option<usize>,
+    category: Option<String>,
+    language: Option<String>,
+}
+
+impl Sift {
+    /// The `admits` method returns `true` if `quote` satisfies the criteria of `self`.
+    pub fn admits(&self, quote: &Quote) -> bool {
+        let author = quote.author().as_deref().unwrap_or("Unknown");
+        let text = quote.quote().to_lowercase();
+        let len = quote.quote().chars().count();
+        let same = |a: &Option<String>, b: &Option<String>| match (a, b) {
+            (Some(want), Some(have)) => want.eq_ignore_ascii_case(have),
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+        (self.authors.is_empty() || self.authors.iter().any(|a| a.eq_ignore_ascii_case(author)))
+            && (self.tags.is_empty() || self.tags.iter().any(|t| quote.has_tag(t)))
+            && (self.keywords.is_empty()
+                || self
+                    .keywords
+                    .iter()
+                    .any(|k| text.contains(&k.to_lowercase())))
+            && self.min_len.is_none_or(|min| len >= min)
+            && self.max_len.is_none_or(|max| len <= max)
+            && same(&self.category, quote.category())
+            && same(&self.language, quote.language())
+    }
+
+    /// The `apply` method returns the quotes in `quotes` admitted by [`Sift::admits`].
+    pub fn apply(&self, quotes: &Quotes) -> Quotes {
+        quotes.select(|quote| self.admits(quote))
+    }
+}
@@ -0,0 +1,307 @@
+use crate::{Arrive, Quote, Quotes};
+use std::path;
+
+/// The `tome` module provides the [`Tome`] enum, which identifies the file format of a collection
+/// of quotes.
+///
+/// # Reading Quotes in Many Formats
+///
+/// The original [`Quotes::from_path`] method only understood the two-column csv file with
+/// `Author` and `Quote` headers that ships in the `data` directory.  Quotes come in all shapes and
+/// sizes, and the quotes a user has lying around are more likely to be in a JSON export or a text
+/// file than in my favorite csv layout.  The `Tome` enum has a variant for each format we know
+/// how to read:
+///
+/// * **Csv** - The original format, with `Author` and `Quote` columns.
+/// * **Json** - An array of objects with `author` and `quote` fields, or an object with a
+///   `quotes` field holding the array.
+/// * **JsonLines** - One JSON object per line.
+/// * **Toml** - An array of tables under the `quotes` key, i.e. `[[quotes]]`.
+/// * **Yaml** - A sequence of mappings, or a mapping with a `quotes` key holding the sequence.
+/// * **Text** - One quote per line, with an optional `— author` suffix.  Blank lines and lines
+///   starting with `#` are skipped.
+///
+/// We pick the format by file extension using [`Tome::from_path`].  If the extension is missing
+/// or unfamiliar, we sniff the contents of the file using [`Tome::sniff`].
+#[derive(
+    Debug,
+    Default,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    strum_macros::EnumIter,
+    derive_more::Display,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub enum Tome {
+    /// Comma-separated values with `Author` and `Quote` headers.
+    #[default]
+    Csv,
+    /// A JSON array of quotes, or an object with a `quotes` array.
+    Json,
+    /// One JSON quote object per line.
+    JsonLines,
+    /// An array of tables under the `quotes` key.
+    Toml,
+    /// A sequence of quotes, or a mapping with a `quotes` sequence.
+    Yaml,
+    /// One quote per line, with an optional `— author` suffix.
+    Text,
+}
+
+/// The `Shelf` enum lets serde accept quotes either as a bare list, or nested under a `quotes`
+/// key.  TOML requires the nested form, because the root of a TOML document must be a table.
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+enum Shelf {
+    List(Vec<Quote>),
+    Table { quotes: Vec<Quote> },
+}
+
+impl From<Shelf> for Quotes {
+    fn from(shelf: Shelf) -> Self {
+        match shelf {
+            Shelf::List(quotes) => Quotes::new(quotes),
+            Shelf::Table { quotes } => Quotes::new(quotes),
+        }
+    }
+}
+
+/// The `Row` struct pairs the result of reading a single quote with its location in the file.
+///
+/// * The `row` field holds the line number, or the position in the list, starting at one.
+/// * The `entry` field holds the [`Quote`], or a description of why it failed to read.
+#[derive(Debug, Clone, PartialEq, Eq, derive_new::new, derive_getters::Getters)]
+pub struct Row {
+    row: usize,
+    entry: Result<Quote, String>,
+}
+
+/// The `ATTRIBUTIONS` constant lists the separators that may divide a quote from its author in
+/// a plain text file.  We split on the last separator found, so the quote itself may contain
+/// dashes.
+const ATTRIBUTIONS: [&str; 3] = [" — ", " -- ", " ― "];
+
+/// The `QUOTE_MARKS` constant lists the pairs of quotation marks that may surround a quote in a
+/// plain text file.
+const QUOTE_MARKS: [(char, char); 4] = [('"', '"'), ('\'', '\''), ('“', '”'), ('‘', '’')];
+
+impl Tome {
+    /// The `from_extension` method matches a file extension to a `Tome` variant, ignoring case.
+    /// Returns [`None`] for extensions we do not recognize.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_lowercase().as_str() {
+            "csv" => Some(Self::Csv),
+            "json" => Some(Self::Json),
+            "jsonl" | "ndjson" => Some(Self::JsonLines),
+            "toml" => Some(Self::Toml),
+            "yaml" | "yml" => Some(Self::Yaml),
+            "txt" | "text" => Some(Self::Text),
+            _ => None,
+        }
+    }
+
+    /// The `from_path` method determines the format of the file at `path` from its extension,
+    /// falling back on [`Tome::sniff`] of the `contents` when the extension does not help.
+    pub fn from_path(path: &path::Path, contents: &str) -> Self {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(Self::from_extension)
+            .unwrap_or_else(|| Self::sniff(contents))
+    }
+
+    /// The `sniff` method guesses the format of `contents` by peeking at the first meaningful
+    /// line.  The purpose of this method is to read files that lack a helpful extension.
+    ///
+    /// The heuristics are simple, and not meant to be bulletproof:
+    ///
+    /// * A leading `{` on every line indicates JSON Lines, otherwise a leading `[` or `{`
+    ///   indicates JSON.  A file with a single line counts as JSON Lines if that line holds a
+    ///   quote on its own.
+    /// * A `[[quotes]]` header or a `quotes =` key indicates TOML.
+    /// * A leading `- ` or `---` indicates YAML, as does a `quotes:` key.
+    /// * A header row mentioning both `Author` and `Quote` separated by a comma indicates csv.
+    /// * Anything else is plain text.
+    pub fn sniff(contents: &str) -> Self {
+        let lines = contents
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .collect::<Vec<&str>>();
+        let first = match lines.first() {
+            Some(line) => *line,
+            None => return Self::Text,
+        };
+        let single = || serde_json::from_str::<Quote>(first).is_ok();
+        if lines.iter().all(|l| l.starts_with('{')) && (lines.len() > 1 || single()) {
+            Self::JsonLines
+        } else if (first.starts_with('[') && !first.starts_with("[[")) || first.starts_with('{') {
+            Self::Json
+        } else if first.starts_with("[[quotes]]") || first.starts_with("quotes =") {
+            Self::Toml
+        } else if first.starts_with("- ") || first.starts_with("---") || first == "quotes:" {
+            Self::Yaml
+        } else {
+            let header = first.to_lowercase();
+            if header.contains(',') && header.contains("author") && header.contains("quote") {
+                Self::Csv
+            } else {
+                Self::Text
+            }
+        }
+    }
+
+    /// The `read` method parses `contents` into [`Quotes`] according to the format of `self`.
+    ///
+    /// Line-oriented formats (csv, JSON Lines and plain text) warn about bad rows and carry on,
+    /// consistent with the original behavior of [`Quotes::from_path`].  The document formats
+    /// (JSON, TOML and YAML) either parse or they do not, and will [`crate::Blame::Json`],
+    /// [`crate::Blame::Toml`] or [`crate::Blame::Yaml`] when they do not.
+    #[tracing::instrument(skip(contents))]
+    pub fn read(&self, contents: &str) -> Arrive<Quotes> {
+        let mut quotes = Vec::new();
+        for row in self.rows(contents)? {
+            match row.entry {
+                Ok(quote) => quotes.push(quote),
+                Err(e) => tracing::warn!("Problem reading quotes on row {}: {e}", row.row),
+            }
+        }
+        tracing::trace!("Read {} quotes as {self}.", quotes.len());
+        Ok(Quotes::new(quotes))
+    }
+
+    /// The `rows` method parses `contents` into a vector of [`Row`], one for each quote we found,
+    /// whether or not the quote read successfully.  The purpose of this method is to keep track of
+    /// where each quote came from, so that a [`crate::Audit`] can point the user at the right
+    /// line.
+    ///
+    /// For csv, JSON Lines and plain text, the row is the line number in the file.  For JSON,
+    /// TOML and YAML, the row is the position of the quote in the list, starting at one, and
+    /// the document must parse as a whole for any rows to come back.
+    pub fn rows(&self, contents: &str) -> Arrive<Vec<Row>> {
+        let numbered = |quotes: Quotes| {
+            quotes
+                .iter()
+                .enumerate()
+                .map(|(idx, quote)| Row::new(idx + 1, Ok(quote.clone())))
+                .collect()
+        };
+        let rows = match self {
+            Self::Csv => {
+                let mut rdr = csv::Reader::from_reader(contents.as_bytes());
+                let mut rows = Vec::new();
+                let mut records = rdr.deserialize::<Quote>();
+                loop {
+                    let line = records.reader().position().line() as usize;
+                    match records.next() {
+                        Some(result) => {
+                            rows.push(Row::new(line, result.map_err(|e| e.to_string())))
+                        }
+                        None => break,
+                    }
+                }
+                rows
+            }
+            Self::Json => numbered(serde_json::from_str::<Shelf>(contents)?.into()),
+            Self::JsonLines => contents
+                .lines()
+                .enumerate()
+                .filter(|(_, l)| !l.trim().is_empty())
+                .map(|(idx, line)| {
+                    let entry = serde_json::from_str::<Quote>(line).map_err(|e| e.to_string());
+                    Row::new(idx + 1, entry)
+                })
+                .collect(),
+            Self::Toml => numbered(toml::from_str::<Shelf>(contents)?.into()),
+            Self::Yaml => numbered(serde_yaml::from_str::<Shelf>(contents)?.into()),
+            Self::Text => contents
+                .lines()
+                .enumerate()
+                .map(|(idx, line)| (idx, line.trim()))
+                .filter(|(_, l)| !l.is_empty() && !l.starts_with('#'))
+                .map(|(idx, line)| Row::new(idx + 1, Ok(Self::inscription(line))))
+                .collect(),
+        };
+        Ok(rows)
+    }
+
+    /// The `inscription` method reads a single line of plain text into a [`Quote`], splitting the
+    /// author from the quote on the last of the [`ATTRIBUTIONS`] separators.  A matching pair of
+    /// [`QUOTE_MARKS`] around the quote is trimmed, while a lone mark, like the apostrophe ending
+    /// "the Joneses'", stays put.
+    fn inscription(line: &str) -> Quote {
+        let split = ATTRIBUTIONS
+            .iter()
+            .filter_map(|sep| line.rfind(sep).map(|idx| (idx, sep.len())))
+            .max_by_key(|(idx, _)| *idx);
+        let (quote, author) = match split {
+            Some((idx, len)) => {
+                let author = line[idx + len..].trim();
+                let author = (!author.is_empty()).then(|| author.to_string());
+                (&line[..idx], author)
+            }
+            None => (line, None),
+        };
+        let quote = quote.trim();
+        let quote = QUOTE_MARKS
+            .iter()
+            .find_map(|(open, close)| {
+                quote
+                    .strip_prefix(*open)
+                    .and_then(|q| q.strip_suffix(*close))
+            })
+            .map_or(quote, str::trim);
+        Quote::new(author, quote.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sniff_single_line_json_lines() {
+        let line = r#"{"author": "Ada", "quote": "Hello."}"#;
+        assert_eq!(Tome::sniff(line), Tome::JsonLines);
+        assert_eq!(Tome::JsonLines.read(line).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn sniff_single_line_json_object() {
+        let doc = r#"{"quotes": [{"author": "Ada", "quote": "Hello."}]}"#;
+        assert_eq!(Tome::sniff(doc), Tome::Json);
+        assert_eq!(Tome::Json.read(doc).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn sniff_formats() {
+        assert_eq!(Tome::sniff("[[quotes]]\nauthor = \"Ada\""), Tome::Toml);
+        assert_eq!(Tome::sniff("- author: Ada"), Tome::Yaml);
+        assert_eq!(Tome::sniff("Author,Quote\nAda,Hello."), Tome::Csv);
+        assert_eq!(Tome::sniff("Hello. — Ada"), Tome::Text);
+        assert_eq!(Tome::sniff(""), Tome::Text);
+    }
+
+    #[test]
+    fn inscription_strips_matching_marks() {
+        let quote = Tome::inscription("\"Be here now.\" — Ram Dass");
+        assert_eq!(quote.quote(), "Be here now.");
+        assert_eq!(quote.author().as_deref(), Some("Ram Dass"));
+        assert_eq!(Tome::inscription("“Curly.”").quote(), "Curly.");
+    }
+
+    #[test]
+    fn inscription_keeps_lone_apostrophe() {
+        let quote = Tome::inscription("Keeping up with the Joneses'");
+        assert_eq!(quote.quote(), "Keeping up with the Joneses'");
+        assert_eq!(
+            Tome::inscription("'Tis the season").quote(),
+            "'Tis the season"
+        );
+    }
+}